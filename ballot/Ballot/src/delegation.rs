@@ -0,0 +1,93 @@
+use soroban_sdk::{vec, Address, Env, Symbol, Vec};
+
+use crate::storage::{self, Choice, VCounter};
+use crate::Error;
+
+/// Checks that delegating `o_voter` to `d_voter` neither closes a cycle nor builds a chain longer than
+/// `max_depth`.
+pub fn check_chain(env: &Env, o_voter: &Address, d_voter: &Address, max_depth: u32) -> Result<(), Error> {
+    let height = storage::get_height(env, o_voter);
+    let mut hops: u32 = 0;
+    let mut node = Some(d_voter.clone());
+    while let Some(n) = node {
+        if n == *o_voter {
+            return Err(Error::DelegationCycle);
+        }
+
+        if height + 1 + hops > max_depth {
+            return Err(Error::DelegationTooDeep);
+        }
+
+        hops += 1;
+        node = storage::get_delegate(env, &n);
+    }
+
+    Ok(())
+}
+
+/// Raises the chain heights above `d_voter` once `o_voter` has delegated to it.
+pub fn update_heights(env: &Env, o_voter: &Address, d_voter: &Address) {
+    let mut height = storage::get_height(env, o_voter) + 1;
+    let mut node = Some(d_voter.clone());
+    while let Some(n) = node {
+        if storage::get_height(env, &n) < height {
+            storage::store_height(env, &n, height);
+        }
+
+        height += 1;
+        node = storage::get_delegate(env, &n);
+    }
+}
+
+/// Walks up the delegation chain starting at `delegate` adding (or removing) `weight` to every delegate until
+/// the first one who voted, whose ballot and counters carry the weight.
+pub fn move_weight(env: &Env, delegate: &Address, weight: u64, add: bool) {
+    let mut node = Some(delegate.clone());
+    while let Some(n) = node {
+        let received = storage::get_received(env, &n);
+        storage::store_received(env, &n, apply(received, weight, add));
+
+        if let Some(mut record) = storage::get_vote(env, &n) {
+            let candidates = counted_candidates(env, &record.choice);
+            if !candidates.is_empty() {
+                let cast = apply(storage::get_cast_weight(env), weight, add);
+                storage::store_cast_weight(env, cast);
+            }
+
+            for candidate in candidates.iter() {
+                let candidate_key = VCounter::Counter(candidate);
+                let count = storage::get_candidate_votes_count(env, &candidate_key);
+                storage::update_candidate_count(env, candidate_key, apply(count, weight, add));
+            }
+
+            if let Choice::Ranked(preferences) = &record.choice {
+                storage::store_ranked_weight(env, preferences, apply(storage::get_ranked_weight(env, preferences), weight, add));
+            }
+
+            record.weight = apply(record.weight, weight, add);
+            storage::update_vote(env, &n, &record);
+            return;
+        }
+
+        node = storage::get_delegate(env, &n);
+    }
+}
+
+/// Candidates whose counters hold the weight of a ballot
+fn counted_candidates(env: &Env, choice: &Choice) -> Vec<Symbol> {
+    match choice {
+        Choice::Single(c) => vec![env, c.clone()],
+        Choice::Ranked(preferences) => vec![env, preferences.first().unwrap()],
+        Choice::Approval(candidates) => candidates.clone(),
+        Choice::Abstain => vec![env, storage::ABSTAIN],
+        Choice::Sealed(_) => Vec::new(env)
+    }
+}
+
+fn apply(value: u64, weight: u64, add: bool) -> u64 {
+    if add {
+        return value + weight;
+    }
+
+    value - weight
+}
@@ -0,0 +1,74 @@
+use soroban_sdk::{symbol_short, Address, BytesN, Env, Map, Symbol, Vec};
+
+use crate::storage::{BallotMode, Certificate, Gating, Rules};
+use crate::tally::{RankedOutcome, StvTally};
+
+pub const BALLOT: Symbol = symbol_short!("ballot");
+
+pub fn configured(env: &Env, admin: Address, gating: Gating, ts_start: u64, ts_end: u64, mode: BallotMode, rules: Rules) {
+    env.events().publish((BALLOT, symbol_short!("configure"), admin), (gating, ts_start, ts_end, mode, rules));
+}
+
+pub fn admin_proposed(env: &Env, admin: Address, new_admin: Address) {
+    env.events().publish((BALLOT, symbol_short!("adm_prop"), admin), new_admin);
+}
+
+pub fn admin_changed(env: &Env, old_admin: Address, new_admin: Address) {
+    env.events().publish((BALLOT, symbol_short!("adm_set"), old_admin), new_admin);
+}
+
+pub fn voted(env: &Env, voter: Address, candidate: Symbol, weight: u64) {
+    env.events().publish((BALLOT, symbol_short!("voted"), voter), (candidate, weight));
+}
+
+pub fn vote_changed(env: &Env, voter: Address, old_candidate: Symbol, new_candidate: Symbol, weight: u64) {
+    env.events().publish((BALLOT, symbol_short!("changed"), voter), (old_candidate, new_candidate, weight));
+}
+
+pub fn committed(env: &Env, voter: Address, commitment: BytesN<32>) {
+    env.events().publish((BALLOT, symbol_short!("committed"), voter), commitment);
+}
+
+pub fn revealed(env: &Env, voter: Address, candidate: Symbol, weight: u64) {
+    env.events().publish((BALLOT, symbol_short!("revealed"), voter), (candidate, weight));
+}
+
+pub fn voted_anonymous(env: &Env, nullifier: BytesN<32>, candidate: Symbol) {
+    env.events().publish((BALLOT, symbol_short!("anon"), nullifier), candidate);
+}
+
+pub fn voted_ranked(env: &Env, voter: Address, preferences: Vec<Symbol>, weight: u64) {
+    env.events().publish((BALLOT, symbol_short!("ranked"), voter), (preferences, weight));
+}
+
+pub fn voted_approval(env: &Env, voter: Address, candidates: Vec<Symbol>, weight: u64) {
+    env.events().publish((BALLOT, symbol_short!("approval"), voter), (candidates, weight));
+}
+
+pub fn abstained(env: &Env, voter: Address, weight: u64) {
+    env.events().publish((BALLOT, symbol_short!("abstained"), voter), weight);
+}
+
+pub fn refunded(env: &Env, voter: Address, amount: i128) {
+    env.events().publish((BALLOT, symbol_short!("refunded"), voter), amount);
+}
+
+pub fn delegated(env: &Env, o_voter: Address, d_voter: Address) {
+    env.events().publish((BALLOT, symbol_short!("delegated"), o_voter), d_voter);
+}
+
+pub fn certified(env: &Env, admin: Address, certificate: Certificate) {
+    env.events().publish((BALLOT, symbol_short!("certified"), admin), certificate);
+}
+
+pub fn counted(env: &Env, count: Map<Symbol, u64>) {
+    env.events().publish((BALLOT, symbol_short!("counted")), count);
+}
+
+pub fn tallied(env: &Env, outcome: RankedOutcome) {
+    env.events().publish((BALLOT, symbol_short!("tallied")), outcome);
+}
+
+pub fn elected(env: &Env, tally: &StvTally) {
+    env.events().publish((BALLOT, symbol_short!("elected")), (tally.quota, tally.elected.clone()));
+}
@@ -1,8 +1,12 @@
 #![no_std]
 
-use soroban_sdk::{contract, contractimpl, contracterror, Env, Symbol, Map, Address, Vec};
+use soroban_sdk::{contract, contractimpl, contracterror, token::TokenClient, xdr::ToXdr, BytesN, Env, Symbol, Map, Address, Vec};
 
+mod delegation;
+mod events;
+mod merkle;
 mod storage;
+mod tally;
 mod validation;
 
 mod token {
@@ -11,7 +15,8 @@ mod token {
     );
 }
 
-use storage::VCounter;
+use storage::{BallotMode, Candidate, CandidateInfo, Certificate, Choice, DelegationEntry, Gating, Quorum, RankedBallot, Rules, TokenGate, TokenRelease, VCounter, VoteRecord, VoterEntry, Weighting};
+use tally::{BallotResult, RankedOutcome, RankedTally, StvTally};
 
 struct Voter<'a> {
     id: &'a Address
@@ -20,43 +25,246 @@ struct Voter<'a> {
 impl<'a> Voter<'a> {
     
     fn has_voted(&self, env: &Env) -> bool {
-        let vts: Vec<Address> = storage::get_votes(env);
-        vts.contains(self.id)
+        storage::has_voted(env, self.id)
     }
 
     fn is_delegated(&self, env: &Env) -> bool {
-        let token = storage::get_token(&env);
-        let tk = token::Client::new(&env, &token);
+        storage::get_delegate(env, self.id).is_some()
+    }
 
-        if tk.blocking(&self.id) {
-            return true
-        }
+    /// Own weight plus the weight delegated by voters who have not voted themselves
+    fn weight(&self, env: &Env, own: u64) -> u64 {
+        own + storage::get_received(env, self.id)
+    }
 
-        false
+    /// A delegated voter who votes directly takes its weight back from the delegation chain
+    fn withdraw_delegation(&self, env: &Env, weight: u64) {
+        if let Some(delegate) = storage::get_delegate(env, self.id) {
+            delegation::move_weight(env, &delegate, weight, false);
+        }
     }
+}
+
+/// Adds `weight` to the weight of the counted ballots
+fn add_cast_weight(env: &Env, weight: u64) {
+    storage::store_cast_weight(env, storage::get_cast_weight(env) + weight);
+}
+
+/// Checks the voter can take part and returns its own voting weight.
+/// BallotToken holders can vote as long as they got their token before the voting window opened, so a token
+/// transferred once the ballot has started cannot be used to vote twice. Generic SEP-41 tokens keep no holding
+/// history, so the balance is checked and then escrowed by the ballot when the vote is cast.
+fn check_eligibility(env: &Env, voter: &Address) -> Result<u64, Error> {
+    match storage::get_gating(env) {
+        Gating::BallotToken(token) => {
+            let tk = token::Client::new(env, &token);
+            if tk.balance(voter) < 1 {
+                return Err(Error::VoterDoesNotHoldToken);
+            }
+
+            let from = storage::get_config(env).from;
+            if from > 0 && tk.holder_since(voter) >= from {
+                return Err(Error::VoterNotEligible);
+            }
 
-    fn has_delegated_vote(&self, env: &Env) -> bool {
-        let token = storage::get_token(&env);
-        let tk = token::Client::new(&env, &token);
+            Ok(1)
+        }
+        Gating::Sep41(gate) => {
+            let balance = TokenClient::new(env, &gate.token).balance(voter);
+            if balance < gate.min_balance {
+                return Err(Error::VoterDoesNotHoldToken);
+            }
 
-        if tk.allowance(&self.id) {
-            return true
+            Ok(token_weight(balance / gate.min_balance, gate.weighting))
         }
+        Gating::Merkle(_) => Err(Error::AnonymousBallot)
+    }
+}
 
-        false
+/// Moves the SEP-41 balance of the voter into the ballot until the voting window closes, so the same tokens
+/// cannot be transferred to another address and vote again.
+fn escrow_tokens(env: &Env, voter: &Address) {
+    if let Gating::Sep41(gate) = storage::get_gating(env) {
+        let tk = TokenClient::new(env, &gate.token);
+        let balance = tk.balance(voter);
+        tk.transfer(voter, &env.current_contract_address(), &balance);
+        storage::store_escrow(env, voter, balance);
     }
 }
 
+/// Weight for a balance of `units` times the minimum balance. Balances are counted up to u32::MAX units, so
+/// with at most u32::MAX ballots the weight counters cannot overflow.
+fn token_weight(units: i128, weighting: Weighting) -> u64 {
+    let units = u32::try_from(units).unwrap_or(u32::MAX);
+    match weighting {
+        Weighting::Flat => 1,
+        Weighting::Linear => units as u64,
+        Weighting::Sqrt => isqrt(units) as u64
+    }
+}
+
+fn isqrt(n: u32) -> u32 {
+    let mut root = 0u32;
+    let mut bit = 1u32 << 15;
+    while bit > 0 {
+        let candidate = root | bit;
+        if (candidate as u64) * (candidate as u64) <= n as u64 {
+            root = candidate;
+        }
+        bit >>= 1;
+    }
+    root
+}
+
+/// Range of indexes of a page over an index holding `total` entries. Pages are capped to `MAX_PAGE_SIZE`
+/// entries so that reading one has a bounded cost.
+fn page_range(total: u32, offset: u32, limit: u32) -> core::ops::Range<u32> {
+    let start = offset.min(total);
+    let end = start.saturating_add(limit.min(storage::MAX_PAGE_SIZE)).min(total);
+    start..end
+}
+
+/// Finalized ballots take no more votes nor delegations
 fn check_dates(env: &Env) -> bool {
+    if storage::get_certificate(env).is_some() {
+        return false;
+    }
+
     let cfg = storage::get_config(env);
     let mut valid = true;
     if cfg.from > 0 && cfg.to > 0 {
-        valid = validation::is_valid_date(&env, &cfg.from, &cfg.to)
+        valid = validation::is_valid_date(env, &cfg.from, &cfg.to)
     }
 
     valid
 }
 
+fn has_started(env: &Env) -> bool {
+    let cfg = storage::get_config(env);
+    cfg.from > 0 && env.ledger().timestamp() >= cfg.from
+}
+
+/// Commit-reveal ballots end once their reveal period is over
+fn has_ended(env: &Env) -> bool {
+    let cfg = storage::get_config(env);
+    cfg.to > 0 && env.ledger().timestamp() > cfg.to + cfg.rules.reveal_period
+}
+
+fn is_commit_reveal(env: &Env) -> bool {
+    storage::get_config(env).rules.reveal_period > 0
+}
+
+fn is_revealing(env: &Env) -> bool {
+    let cfg = storage::get_config(env);
+    let now = env.ledger().timestamp();
+    cfg.rules.reveal_period > 0 && now > cfg.to && now <= cfg.to + cfg.rules.reveal_period
+}
+
+/// Commitment a voter submits for a candidate: `sha256(xdr(candidate) || salt)`
+fn commitment(env: &Env, candidate: &Symbol, salt: &BytesN<32>) -> BytesN<32> {
+    let mut data = candidate.clone().to_xdr(env);
+    data.extend_from_array(&salt.to_array());
+    env.crypto().sha256(&data)
+}
+
+fn vote_counts(env: &Env) -> Map<Symbol, u64> {
+    let mut count_map: Map<Symbol, u64> = Map::new(env);
+    for party in storage::get_candidates(env).iter() {
+        let candidate_key = VCounter::Counter(party.clone());
+        let candidate_count: u64 = storage::get_candidate_votes_count(env, &candidate_key);
+        count_map.set(party, candidate_count);
+    }
+
+    count_map
+}
+
+/// Ids used as keys of the tally map cannot be registered as candidates
+fn is_reserved(candidate: &Symbol) -> bool {
+    *candidate == storage::TURNOUT || *candidate == storage::ABSTAIN || *candidate == storage::SEALED
+}
+
+fn candidate_record(env: &Env, id: Symbol) -> Candidate {
+    let mut votes = 0;
+    if has_ended(env) {
+        votes = storage::get_candidate_votes_count(env, &VCounter::Counter(id.clone()));
+    }
+
+    Candidate { info: storage::get_candidate_info(env, &id), id, votes }
+}
+
+/// Candidate counts together with the abstentions and the turnout. Commit-reveal ballots also report the
+/// commitments that were never revealed.
+fn tally_map(env: &Env) -> Map<Symbol, u64> {
+    let mut count_map = vote_counts(env);
+    count_map.set(storage::ABSTAIN, storage::get_candidate_votes_count(env, &VCounter::Counter(storage::ABSTAIN)));
+    count_map.set(storage::TURNOUT, storage::get_turnout(env) as u64);
+    if is_commit_reveal(env) {
+        count_map.set(storage::SEALED, storage::get_candidate_votes_count(env, &VCounter::Counter(storage::SEALED)));
+    }
+
+    count_map
+}
+
+/// Weight of the ballots cast for candidates. Approval ballots add their weight to several counters, so it
+/// is taken from the aggregate cast weight rather than summed from the counters.
+fn cast_weight(env: &Env) -> u64 {
+    storage::get_cast_weight(env) - storage::get_candidate_votes_count(env, &VCounter::Counter(storage::ABSTAIN))
+}
+
+/// Candidate lists must be non empty, registered and free of duplicates
+fn check_candidate_list(env: &Env, list: &Vec<Symbol>) -> Result<(), Error> {
+    if list.is_empty() {
+        return Err(Error::InvalidPreferences);
+    }
+
+    let candidates = storage::get_candidates(env);
+    for (i, p) in list.iter().enumerate() {
+        if !candidates.contains(&p) {
+            return Err(Error::InvalidCandidate);
+        }
+
+        if list.first_index_of(&p) != Some(i as u32) {
+            return Err(Error::InvalidPreferences);
+        }
+    }
+
+    Ok(())
+}
+
+/// Approvals name each candidate once, however many times the voter listed it
+fn unique_candidates(env: &Env, list: &Vec<Symbol>) -> Vec<Symbol> {
+    let mut unique: Vec<Symbol> = Vec::new(env);
+    for candidate in list.iter() {
+        if !unique.contains(&candidate) {
+            unique.push_back(candidate);
+        }
+    }
+
+    unique
+}
+
+/// Ranked ballots sharing a preference list are tallied as a single ballot carrying their whole weight
+fn ranked_ballots(env: &Env) -> Vec<RankedBallot> {
+    let mut ballots: Vec<RankedBallot> = Vec::new(env);
+    for index in 0..storage::get_ranked_count(env) {
+        let ballot = storage::get_ranked_at(env, index);
+        if ballot.weight > 0 {
+            ballots.push_back(ballot);
+        }
+    }
+
+    ballots
+}
+
+fn check_admin(env: &Env, admin: &Address) -> Result<(), Error> {
+    admin.require_auth();
+    match storage::get_admin(env) {
+        Some(a) if a == *admin => Ok(()),
+        Some(_) => Err(Error::AddressIsNotAdmin),
+        None => Err(Error::BallotNotConfigured)
+    }
+}
+
 
 #[contracterror]
 #[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
@@ -68,8 +276,33 @@ pub enum Error {
     VoterOriginHasAlreadyVotedAndCannotDelegate = 4,
     VoterTargetHasAlreadyVotedAndCannotDelegate = 5,
     BallotOutOfDate = 6,
-    VoterDoesNotHoldToken = 7
-
+    VoterDoesNotHoldToken = 7,
+    InvalidBallotMode = 8,
+    InvalidPreferences = 9,
+    InvalidCandidate = 10,
+    CandidatesLocked = 11,
+    AddressIsNotAdmin = 12,
+    BallotAlreadyStarted = 13,
+    BallotNotConfigured = 14,
+    NoPendingAdmin = 15,
+    InvalidRules = 16,
+    BallotNotClosed = 17,
+    VoterHasNotVoted = 18,
+    DelegationCycle = 19,
+    DelegationTooDeep = 20,
+    TooManyApprovals = 21,
+    VoterNotEligible = 22,
+    InvalidGating = 23,
+    DelegationNotAvailable = 24,
+    AnonymousBallot = 25,
+    InvalidMerkleProof = 26,
+    NullifierAlreadySpent = 27,
+    CommitRevealBallot = 28,
+    VoteNotCommitted = 29,
+    InvalidReveal = 30,
+    BallotFinalized = 31,
+    BallotNotFinalized = 32,
+    NothingToRefund = 33
 }
 
 #[contract]
@@ -78,21 +311,171 @@ pub struct Ballot;
 #[contractimpl]
 impl Ballot {
 
-    pub fn configure(env: Env, admin: Address, token: Address, ts_start: u64, ts_end: u64) -> Result<bool, Error> {
-        admin.require_auth();
-        storage::store_config(&env, ts_start, ts_end, token);
+    /// Configures the ballot. The first call sets the admin, later calls must come from that admin
+    /// and are only accepted until the voting window opens.
+    /// Percentage quorums need the BallotToken supply and are rejected with any other gating.
+    pub fn configure(env: Env, admin: Address, gating: Gating, ts_start: u64, ts_end: u64, mode: BallotMode, rules: Rules) -> Result<bool, Error> {
+        if rules.threshold > tally::MAX_BPS {
+            return Err(Error::InvalidRules);
+        }
+
+        if let Quorum::Percentage(bps) = rules.quorum {
+            if bps > tally::MAX_BPS || !matches!(gating, Gating::BallotToken(_)) {
+                return Err(Error::InvalidRules);
+            }
+        }
+
+        if let Gating::Sep41(TokenGate { min_balance, .. }) = gating {
+            if min_balance <= 0 {
+                return Err(Error::InvalidGating);
+            }
+        }
+
+        if mode == BallotMode::Approval(0) || mode == BallotMode::Stv(0) {
+            return Err(Error::InvalidBallotMode);
+        }
+
+        // Anonymous votes carry a single candidate
+        if matches!(gating, Gating::Merkle(_)) && mode != BallotMode::Plurality {
+            return Err(Error::InvalidBallotMode);
+        }
+
+        // Seats are filled by quota, a winning threshold does not apply
+        if let BallotMode::Stv(_) = mode {
+            if rules.threshold > 0 {
+                return Err(Error::InvalidRules);
+            }
+        }
+
+        if storage::get_certificate(&env).is_some() {
+            return Err(Error::BallotFinalized);
+        }
+
+        if rules.reveal_period > 0 && (mode != BallotMode::Plurality || matches!(gating, Gating::Merkle(_))) {
+            return Err(Error::InvalidRules);
+        }
+
+        if storage::get_admin(&env).is_some() {
+            check_admin(&env, &admin)?;
+            if has_started(&env) {
+                return Err(Error::BallotAlreadyStarted);
+            }
+        } else {
+            admin.require_auth();
+            storage::store_admin(&env, &admin);
+        }
+
+        storage::store_config(&env, ts_start, ts_end, mode, rules.clone(), gating.clone());
+        events::configured(&env, admin, gating, ts_start, ts_end, mode, rules);
+        Ok(true)
+    }
+
+    /// Registers the candidates voters can choose from. The list can only be edited before the voting window opens.
+    pub fn register_candidates(env: Env, admin: Address, candidates: Vec<Symbol>) -> Result<bool, Error> {
+        check_admin(&env, &admin)?;
+
+        if has_started(&env) {
+            return Err(Error::CandidatesLocked);
+        }
+
+        if candidates.iter().any(|c| is_reserved(&c)) {
+            return Err(Error::InvalidCandidate);
+        }
+
+        for candidate in candidates.iter() {
+            storage::store_party(&env, &candidate);
+        }
+
+        Ok(true)
+    }
+
+    /// Registers the candidate if needed and stores its details. Like the candidate list, details can only be
+    /// edited before the voting window opens.
+    pub fn set_candidate(env: Env, admin: Address, candidate: Symbol, info: CandidateInfo) -> Result<bool, Error> {
+        check_admin(&env, &admin)?;
+
+        if has_started(&env) {
+            return Err(Error::CandidatesLocked);
+        }
+
+        if is_reserved(&candidate) {
+            return Err(Error::InvalidCandidate);
+        }
+
+        storage::store_party(&env, &candidate);
+        storage::store_candidate_info(&env, &candidate, &info);
+        Ok(true)
+    }
+
+    pub fn remove_candidate(env: Env, admin: Address, candidate: Symbol) -> Result<bool, Error> {
+        check_admin(&env, &admin)?;
+
+        if has_started(&env) {
+            return Err(Error::CandidatesLocked);
+        }
+
+        if !storage::remove_party(&env, &candidate) {
+            return Err(Error::InvalidCandidate);
+        }
+
+        storage::remove_candidate_info(&env, &candidate);
+        Ok(true)
+    }
+
+    /// First step of an admin transfer, the new admin has to accept it with accept_admin
+    pub fn propose_admin(env: Env, admin: Address, new_admin: Address) -> Result<bool, Error> {
+        check_admin(&env, &admin)?;
+        storage::store_pending_admin(&env, &new_admin);
+        events::admin_proposed(&env, admin, new_admin);
         Ok(true)
     }
 
+    pub fn accept_admin(env: Env, new_admin: Address) -> Result<bool, Error> {
+        new_admin.require_auth();
+
+        match storage::get_pending_admin(&env) {
+            Some(p) if p == new_admin => {
+                let old_admin = storage::get_admin(&env).unwrap();
+                storage::store_admin(&env, &new_admin);
+                storage::remove_pending_admin(&env);
+                events::admin_changed(&env, old_admin, new_admin);
+                Ok(true)
+            },
+            Some(_) => Err(Error::AddressIsNotAdmin),
+            None => Err(Error::NoPendingAdmin)
+        }
+    }
+
+    pub fn candidate(env: Env, id: Symbol) -> Result<Candidate, Error> {
+        if !storage::get_candidates(&env).contains(&id) {
+            return Err(Error::InvalidCandidate);
+        }
+
+        Ok(candidate_record(&env, id))
+    }
+
+    /// Registered candidates with their details, vote counts are only given once the ballot is closed
+    pub fn candidates(env: Env) -> Vec<Candidate> {
+        let mut records: Vec<Candidate> = Vec::new(&env);
+        for id in storage::get_candidates(&env).iter() {
+            records.push_back(candidate_record(&env, id));
+        }
+
+        records
+    }
+
     pub fn vote(env: Env, voter: Address, candidate: Symbol) -> Result<bool, Error> {
         voter.require_auth();
 
-        let token = storage::get_token(&env);
-        let tk = token::Client::new(&env, &token);
+        if storage::get_config(&env).mode != BallotMode::Plurality {
+            return Err(Error::InvalidBallotMode);
+        }
 
-        if tk.balance(&voter) < 1 {
-            return Err(Error::VoterDoesNotHoldToken);
+        if is_commit_reveal(&env) {
+            return Err(Error::CommitRevealBallot);
         }
+
+        let own = check_eligibility(&env, &voter)?;
         
         if !check_dates(&env) {
             return Err(Error::BallotOutOfDate);
@@ -100,46 +483,351 @@ impl Ballot {
 
         let v: Voter = Voter { id: &voter };
 
-        if v.is_delegated(&env) {
-            return Err(Error::VoterHasHisVoteDelegated)
+        if v.has_voted(&env) {
+            return Err(Error::VoterHasAlreadyVoted)
+        }
+
+        if !storage::get_candidates(&env).contains(&candidate) {
+            return Err(Error::InvalidCandidate)
+        }
+
+        let candidate_key: VCounter = VCounter::Counter(candidate.clone());
+        let weight = v.weight(&env, own);
+        v.withdraw_delegation(&env, weight);
+        add_cast_weight(&env, weight);
+        let count = weight + storage::get_candidate_votes_count(&env, &candidate_key);
+ 
+        storage::update_candidate_count(&env, candidate_key, count);
+        storage::store_vote(&env, &voter, &VoteRecord { choice: Choice::Single(candidate.clone()), weight });
+        escrow_tokens(&env, &voter);
+        events::voted(&env, voter, candidate, weight);
+
+        Ok(true)
+    }
+
+    /// Casts a plurality vote in a Merkle gated ballot without revealing the voter's Stellar account. The voter
+    /// proves its leaf, `sha256(voter_key || nullifier)`, is in the allowlist and signs the candidate with its
+    /// ed25519 `voter_key`, so the `relayer` submitting the vote cannot change it. The nullifier is recorded
+    /// as spent instead of an address. Votes are not anonymous to whoever built the allowlist: knowing the
+    /// key behind each leaf, it can link every vote to its voter.
+    pub fn vote_anonymous(env: Env, relayer: Address, candidate: Symbol, nullifier: BytesN<32>, voter_key: BytesN<32>, proof: Vec<BytesN<32>>, signature: BytesN<64>) -> Result<bool, Error> {
+        relayer.require_auth();
+
+        let cfg = storage::get_config(&env);
+        if cfg.mode != BallotMode::Plurality {
+            return Err(Error::InvalidBallotMode);
+        }
+
+        let Gating::Merkle(root) = storage::get_gating(&env) else {
+            return Err(Error::InvalidGating);
+        };
+
+        if !check_dates(&env) {
+            return Err(Error::BallotOutOfDate);
+        }
+
+        if !merkle::verify(&env, &root, merkle::leaf(&env, &voter_key, &nullifier), &proof) {
+            return Err(Error::InvalidMerkleProof);
+        }
+
+        if storage::is_nullifier_spent(&env, &nullifier) {
+            return Err(Error::NullifierAlreadySpent);
+        }
+
+        if !storage::get_candidates(&env).contains(&candidate) {
+            return Err(Error::InvalidCandidate)
+        }
+
+        // Traps when the voter did not sign this candidate
+        let message = merkle::vote_message(&env, &env.current_contract_address(), &candidate, &nullifier);
+        env.crypto().ed25519_verify(&voter_key, &message, &signature);
+
+        add_cast_weight(&env, 1);
+        let candidate_key: VCounter = VCounter::Counter(candidate.clone());
+        let count = 1 + storage::get_candidate_votes_count(&env, &candidate_key);
+
+        storage::update_candidate_count(&env, candidate_key, count);
+        storage::spend_nullifier(&env, &nullifier);
+        events::voted_anonymous(&env, nullifier, candidate);
+
+        Ok(true)
+    }
+
+    /// Commits a sealed plurality vote in a commit-reveal ballot. The weight delegated to the voter keeps
+    /// following its record and is counted once the vote is revealed.
+    pub fn commit_vote(env: Env, voter: Address, commitment: BytesN<32>) -> Result<bool, Error> {
+        voter.require_auth();
+
+        if !is_commit_reveal(&env) {
+            return Err(Error::InvalidBallotMode);
         }
 
+        let own = check_eligibility(&env, &voter)?;
+
+        if !check_dates(&env) {
+            return Err(Error::BallotOutOfDate);
+        }
+
+        let v: Voter = Voter { id: &voter };
+
         if v.has_voted(&env) {
             return Err(Error::VoterHasAlreadyVoted)
         }
-        
-        storage::store_party(&env, &candidate);
 
-        let mut votes: Vec<Address> = storage::get_votes(&env);
-        let candidate_key: VCounter = VCounter::Counter(candidate);
-        let mut d_votes = 0;
-        if v.has_delegated_vote(&env) {
-            d_votes = 1;
+        let sealed_key: VCounter = VCounter::Counter(storage::SEALED);
+        let sealed = 1 + storage::get_candidate_votes_count(&env, &sealed_key);
+        let weight = v.weight(&env, own);
+        v.withdraw_delegation(&env, weight);
+
+        storage::update_candidate_count(&env, sealed_key, sealed);
+        storage::store_vote(&env, &voter, &VoteRecord { choice: Choice::Sealed(commitment.clone()), weight });
+        escrow_tokens(&env, &voter);
+        events::committed(&env, voter, commitment);
+
+        Ok(true)
+    }
+
+    /// Reveals a committed vote during the reveal period, counting it for the candidate with the weight
+    /// the voter gathered while the ballot was open.
+    pub fn reveal_vote(env: Env, voter: Address, candidate: Symbol, salt: BytesN<32>) -> Result<bool, Error> {
+        voter.require_auth();
+
+        if !is_revealing(&env) {
+            return Err(Error::BallotOutOfDate);
         }
 
-        let count = 1 + d_votes + storage::get_candidate_votes_count(&env, &candidate_key);
-        votes.push_back(voter);
- 
+        let mut record = match storage::get_vote(&env, &voter) {
+            Some(r) => r,
+            None => return Err(Error::VoteNotCommitted)
+        };
+
+        let Choice::Sealed(sealed) = record.choice else {
+            return Err(Error::VoteNotCommitted);
+        };
+
+        if sealed != commitment(&env, &candidate, &salt) {
+            return Err(Error::InvalidReveal);
+        }
+
+        if !storage::get_candidates(&env).contains(&candidate) {
+            return Err(Error::InvalidCandidate)
+        }
+
+        add_cast_weight(&env, record.weight);
+        let candidate_key: VCounter = VCounter::Counter(candidate.clone());
+        let count = record.weight + storage::get_candidate_votes_count(&env, &candidate_key);
+        let sealed_key: VCounter = VCounter::Counter(storage::SEALED);
+        let unrevealed = storage::get_candidate_votes_count(&env, &sealed_key) - 1;
+
         storage::update_candidate_count(&env, candidate_key, count);
-        storage::update_votes(&env, votes);
+        storage::update_candidate_count(&env, sealed_key, unrevealed);
+        record.choice = Choice::Single(candidate.clone());
+        storage::update_vote(&env, &voter, &record);
+        events::revealed(&env, voter, candidate, record.weight);
 
         Ok(true)
     }
 
-    pub fn delegate(env: Env, o_voter: Address, d_voter: Address) -> Result<bool, Error> {
+    /// Moves a plurality vote, together with the weight delegated to the voter, to another candidate.
+    /// Votes can be changed until the ballot closes.
+    pub fn change_vote(env: Env, voter: Address, candidate: Symbol) -> Result<bool, Error> {
+        voter.require_auth();
 
-        o_voter.require_auth();
-        let token = storage::get_token(&env);
-        let tk = token::Client::new(&env, &token);
+        if storage::get_config(&env).mode != BallotMode::Plurality {
+            return Err(Error::InvalidBallotMode);
+        }
+
+        if is_commit_reveal(&env) {
+            return Err(Error::CommitRevealBallot);
+        }
+
+        if !check_dates(&env) {
+            return Err(Error::BallotOutOfDate);
+        }
+
+        let mut record = match storage::get_vote(&env, &voter) {
+            Some(r) => r,
+            None => return Err(Error::VoterHasNotVoted)
+        };
+
+        if !storage::get_candidates(&env).contains(&candidate) {
+            return Err(Error::InvalidCandidate)
+        }
+
+        let old_candidate = match record.choice {
+            Choice::Single(c) => c,
+            Choice::Abstain => storage::ABSTAIN,
+            _ => return Err(Error::InvalidBallotMode)
+        };
+
+        let old_key = VCounter::Counter(old_candidate.clone());
+        let old_count = storage::get_candidate_votes_count(&env, &old_key);
+        storage::update_candidate_count(&env, old_key, old_count - record.weight);
+
+        let new_key = VCounter::Counter(candidate.clone());
+        let new_count = storage::get_candidate_votes_count(&env, &new_key);
+        storage::update_candidate_count(&env, new_key, new_count + record.weight);
+
+        record.choice = Choice::Single(candidate.clone());
+        storage::update_vote(&env, &voter, &record);
+        events::vote_changed(&env, voter, old_candidate, candidate, record.weight);
+
+        Ok(true)
+    }
+
+    /// Casts a ranked ballot. Preferences go from the most to the least preferred candidate and the whole
+    /// ballot carries the voter weight (delegated vote included) through every instant-runoff round.
+    pub fn vote_ranked(env: Env, voter: Address, preferences: Vec<Symbol>) -> Result<bool, Error> {
+        voter.require_auth();
+
+        if !matches!(storage::get_config(&env).mode, BallotMode::RankedChoice | BallotMode::Stv(_)) {
+            return Err(Error::InvalidBallotMode);
+        }
+
+        let own = check_eligibility(&env, &voter)?;
+
+        if !check_dates(&env) {
+            return Err(Error::BallotOutOfDate);
+        }
+
+        let v: Voter = Voter { id: &voter };
+
+        if v.has_voted(&env) {
+            return Err(Error::VoterHasAlreadyVoted)
+        }
+
+        check_candidate_list(&env, &preferences)?;
+
+        let weight = v.weight(&env, own);
+        v.withdraw_delegation(&env, weight);
+        add_cast_weight(&env, weight);
+
+        // First preferences are kept in the counters so that count shows the first round
+        let candidate_key: VCounter = VCounter::Counter(preferences.first().unwrap());
+        let count = weight + storage::get_candidate_votes_count(&env, &candidate_key);
+
+        storage::update_candidate_count(&env, candidate_key, count);
+        storage::store_ranked_weight(&env, &preferences, storage::get_ranked_weight(&env, &preferences) + weight);
+        storage::store_vote(&env, &voter, &VoteRecord { choice: Choice::Ranked(preferences.clone()), weight });
+        escrow_tokens(&env, &voter);
+        events::voted_ranked(&env, voter, preferences, weight);
+
+        Ok(true)
+    }
+
+    /// Approves every candidate in the list, each one gaining the voter's weight.
+    pub fn vote_approval(env: Env, voter: Address, candidates: Vec<Symbol>) -> Result<bool, Error> {
+        voter.require_auth();
+
+        let max_approvals = match storage::get_config(&env).mode {
+            BallotMode::Approval(max) => max,
+            _ => return Err(Error::InvalidBallotMode)
+        };
+
+        let own = check_eligibility(&env, &voter)?;
+
+        if !check_dates(&env) {
+            return Err(Error::BallotOutOfDate);
+        }
+
+        let v: Voter = Voter { id: &voter };
+
+        if v.has_voted(&env) {
+            return Err(Error::VoterHasAlreadyVoted)
+        }
+
+        let candidates = unique_candidates(&env, &candidates);
+        check_candidate_list(&env, &candidates)?;
+        if candidates.len() > max_approvals {
+            return Err(Error::TooManyApprovals);
+        }
+
+        let weight = v.weight(&env, own);
+        v.withdraw_delegation(&env, weight);
+        add_cast_weight(&env, weight);
+        for candidate in candidates.iter() {
+            let candidate_key: VCounter = VCounter::Counter(candidate);
+            let count = weight + storage::get_candidate_votes_count(&env, &candidate_key);
+            storage::update_candidate_count(&env, candidate_key, count);
+        }
+
+        storage::store_vote(&env, &voter, &VoteRecord { choice: Choice::Approval(candidates.clone()), weight });
+        escrow_tokens(&env, &voter);
+        events::voted_approval(&env, voter, candidates, weight);
+
+        Ok(true)
+    }
+
+    /// Casts a blank ballot. It counts toward turnout and quorum, but not for any candidate.
+    pub fn abstain(env: Env, voter: Address) -> Result<bool, Error> {
+        voter.require_auth();
 
-        if tk.balance(&o_voter) < 1 {
-            return Err(Error::VoterDoesNotHoldToken);
+        if is_commit_reveal(&env) {
+            return Err(Error::CommitRevealBallot);
         }
 
-        if tk.balance(&d_voter) < 1 {
-            return Err(Error::VoterDoesNotHoldToken);
+        let own = check_eligibility(&env, &voter)?;
+
+        if !check_dates(&env) {
+            return Err(Error::BallotOutOfDate);
+        }
+
+        let v: Voter = Voter { id: &voter };
+
+        if v.has_voted(&env) {
+            return Err(Error::VoterHasAlreadyVoted)
+        }
+
+        let abstain_key: VCounter = VCounter::Counter(storage::ABSTAIN);
+        let weight = v.weight(&env, own);
+        v.withdraw_delegation(&env, weight);
+        add_cast_weight(&env, weight);
+        let count = weight + storage::get_candidate_votes_count(&env, &abstain_key);
+
+        storage::update_candidate_count(&env, abstain_key, count);
+        storage::store_vote(&env, &voter, &VoteRecord { choice: Choice::Abstain, weight });
+        escrow_tokens(&env, &voter);
+        events::abstained(&env, voter, weight);
+
+        Ok(true)
+    }
+
+    /// Returns the SEP-41 tokens the voter escrowed when voting, once the ballot has ended. A ballot without
+    /// an end date never ends, so its tokens stay escrowed.
+    pub fn refund(env: Env, voter: Address) -> Result<i128, Error> {
+        let Gating::Sep41(gate) = storage::get_gating(&env) else {
+            return Err(Error::InvalidGating);
+        };
+
+        if !has_ended(&env) {
+            return Err(Error::BallotNotClosed);
+        }
+
+        let amount = storage::get_escrow(&env, &voter);
+        if amount == 0 {
+            return Err(Error::NothingToRefund);
+        }
+
+        storage::remove_escrow(&env, &voter);
+        TokenClient::new(&env, &gate.token).transfer(&env.current_contract_address(), &voter, &amount);
+        events::refunded(&env, voter, amount);
+
+        Ok(amount)
+    }
+
+    /// Delegates the vote of `o_voter`, together with the weight it has received, to `d_voter`. Delegations can
+    /// be chained and the weight goes to the first delegate up the chain who votes. A delegator who votes
+    /// directly takes its weight back.
+    pub fn delegate(env: Env, o_voter: Address, d_voter: Address) -> Result<bool, Error> {
+
+        o_voter.require_auth();
+        if let Gating::Sep41(_) = storage::get_gating(&env) {
+            return Err(Error::DelegationNotAvailable);
         }
 
+        let own = check_eligibility(&env, &o_voter)?;
+        check_eligibility(&env, &d_voter)?;
+
         if !check_dates(&env) {
             return Err(Error::BallotOutOfDate);
         }
@@ -162,31 +850,205 @@ impl Ballot {
             return Err(Error::VoterHasHisVoteDelegated)
         }
 
-        if dv.has_delegated_vote(&env) {
-            return Err(Error::VoterHasDelegatedVote)
+        let mut max_depth = storage::get_config(&env).rules.max_depth;
+        if max_depth == 0 {
+            max_depth = storage::DEFAULT_DELEGATION_DEPTH;
         }
 
-        let config = storage::get_config(&env);
-        let expiration_ledger = (((config.to - config.from) / 5) + 60) as u32; // 5 seconds for every ledger. Add 5 extra minutes
+        delegation::check_chain(&env, &o_voter, &d_voter, max_depth)?;
 
-        tk.approve(&o_voter, &d_voter, &expiration_ledger);
+        storage::store_delegate(&env, &o_voter, &d_voter);
+        delegation::update_heights(&env, &o_voter, &d_voter);
+        delegation::move_weight(&env, &d_voter, ov.weight(&env, own), true);
+        events::delegated(&env, o_voter, d_voter);
 
         Ok(true)
 
     }
 
-    pub fn count(env: Env,  admin: Address) -> Map<Symbol, u32> {
+    /// Only the turnout is returned while the voting window is open, candidate counts once the ballot closes.
+    /// This does not make votes secret: vote events and contract storage are public, only commit-reveal
+    /// ballots keep votes hidden until they are revealed.
+    pub fn count(env: Env,  admin: Address) -> Result<Map<Symbol, u64>, Error> {
         
-        admin.require_auth();
-        let pts = storage::get_candidates(&env);
-        let mut count_map: Map<Symbol, u32>= Map::new(&env);
-        for party in pts.iter() {
-            let candidate_key = VCounter::Counter(party.clone());
-            let candidate_count: u32 = storage::get_candidate_votes_count(&env, &candidate_key);
-            count_map.set(party, candidate_count);
+        check_admin(&env, &admin)?;
+        let mut count_map: Map<Symbol, u64> = Map::new(&env);
+        if has_ended(&env) {
+            count_map = tally_map(&env);
+        } else {
+            count_map.set(storage::TURNOUT, storage::get_turnout(&env) as u64);
+        }
+
+        events::counted(&env, count_map.clone());
+        Ok(count_map)
+    }
+
+    /// Public candidate counts, abstentions and turnout, available once the ballot is closed.
+    pub fn results(env: Env) -> Result<Map<Symbol, u64>, Error> {
+        if !has_ended(&env) {
+            return Err(Error::BallotNotClosed);
+        }
+
+        Ok(tally_map(&env))
+    }
+
+    /// Voters in the order they voted, with their choice and the weight of their ballot. Available once the
+    /// ballot is closed.
+    pub fn voters(env: Env, offset: u32, limit: u32) -> Result<Vec<VoterEntry>, Error> {
+        if !has_ended(&env) {
+            return Err(Error::BallotNotClosed);
+        }
+
+        if let Gating::Merkle(_) = storage::get_gating(&env) {
+            return Err(Error::AnonymousBallot);
+        }
+
+        let mut entries: Vec<VoterEntry> = Vec::new(&env);
+        for i in page_range(storage::get_turnout(&env), offset, limit) {
+            let voter = storage::get_voter_at(&env, i);
+            let record = storage::get_vote(&env, &voter).unwrap();
+            entries.push_back(VoterEntry { voter, choice: record.choice, weight: record.weight });
+        }
+
+        Ok(entries)
+    }
+
+    /// Delegations in the order they were made. Available once the ballot is closed.
+    pub fn delegations(env: Env, offset: u32, limit: u32) -> Result<Vec<DelegationEntry>, Error> {
+        if !has_ended(&env) {
+            return Err(Error::BallotNotClosed);
         }
 
-        count_map
+        let mut entries: Vec<DelegationEntry> = Vec::new(&env);
+        for i in page_range(storage::get_delegations_count(&env), offset, limit) {
+            let delegator = storage::get_delegator_at(&env, i);
+            let delegate = storage::get_delegate(&env, &delegator).unwrap();
+            entries.push_back(DelegationEntry { delegator, delegate });
+        }
+
+        Ok(entries)
+    }
+
+    pub fn tally_ranked(env: Env, admin: Address) -> Result<RankedTally, Error> {
+
+        check_admin(&env, &admin)?;
+        if !has_ended(&env) {
+            return Err(Error::BallotNotClosed);
+        }
+
+        if storage::get_config(&env).mode != BallotMode::RankedChoice {
+            return Err(Error::InvalidBallotMode);
+        }
+
+        let tally = tally::instant_runoff(&env, storage::get_candidates(&env), &ranked_ballots(&env));
+        events::tallied(&env, tally.outcome.clone());
+        Ok(tally)
+    }
+
+    /// Runs the single transferable vote count of a multi-seat ballot, returning every round and the
+    /// elected candidates in the order they were elected.
+    pub fn tally_stv(env: Env, admin: Address) -> Result<StvTally, Error> {
+
+        check_admin(&env, &admin)?;
+        if !has_ended(&env) {
+            return Err(Error::BallotNotClosed);
+        }
+
+        let BallotMode::Stv(seats) = storage::get_config(&env).mode else {
+            return Err(Error::InvalidBallotMode);
+        };
+
+        let tally = tally::single_transferable_vote(&env, storage::get_candidates(&env), seats, &ranked_ballots(&env));
+        events::elected(&env, &tally);
+        Ok(tally)
+    }
+
+    /// Applies the quorum and threshold rules once the ballot is closed. Participation is the weight of the
+    /// votes cast, delegated votes and abstentions included, while the threshold only considers the weight
+    /// cast for candidates. Ranked ballots are resolved with the final instant-runoff round,
+    /// approval ballots measure the threshold against the weight of the voters approving the winner and
+    /// multi-seat ballots return the candidates elected by STV.
+    pub fn result(env: Env) -> Result<BallotResult, Error> {
+        if !has_ended(&env) {
+            return Err(Error::BallotNotClosed);
+        }
+
+        if let Some(certificate) = storage::get_certificate(&env) {
+            return Ok(certificate.result);
+        }
+
+        let cfg = storage::get_config(&env);
+        let mut counts = vote_counts(&env);
+
+        let mut cast = cast_weight(&env);
+        let abstained = storage::get_candidate_votes_count(&env, &VCounter::Counter(storage::ABSTAIN));
+        let mut supply: u32 = 0;
+        if let Quorum::Percentage(_) = cfg.rules.quorum {
+            if let Gating::BallotToken(token) = storage::get_gating(&env) {
+                supply = token::Client::new(&env, &token).live_supply(&cfg.to);
+            }
+        }
+
+        if cast == 0 || !tally::is_quorum_reached(&cfg.rules.quorum, cast + abstained, supply) {
+            return Ok(BallotResult::NoQuorum);
+        }
+
+        if let BallotMode::Stv(seats) = cfg.mode {
+            let tally = tally::single_transferable_vote(&env, storage::get_candidates(&env), seats, &ranked_ballots(&env));
+            return Ok(BallotResult::Elected(tally.elected));
+        }
+
+        if cfg.mode == BallotMode::RankedChoice {
+            let tally = tally::instant_runoff(&env, storage::get_candidates(&env), &ranked_ballots(&env));
+            counts = tally.rounds.last().unwrap().counts;
+            cast = counts.values().iter().sum();
+            if let RankedOutcome::Tie(tied) = tally.outcome {
+                return Ok(BallotResult::Tie(tied));
+            }
+        }
+
+        Ok(tally::plurality(&env, &counts, cast, cfg.rules.threshold))
+    }
+
+    /// Writes the final tally and outcome once the ballot is closed. The ballot cannot be configured nor
+    /// take votes afterwards and the BallotToken can be retired, burning its tokens or leaving them with
+    /// their holders, locked or free to transfer.
+    pub fn finalize(env: Env, admin: Address, release: TokenRelease) -> Result<Certificate, Error> {
+        check_admin(&env, &admin)?;
+
+        if !has_ended(&env) {
+            return Err(Error::BallotNotClosed);
+        }
+
+        if storage::get_certificate(&env).is_some() {
+            return Err(Error::BallotFinalized);
+        }
+
+        let gating = storage::get_gating(&env);
+        if release != TokenRelease::Keep && !matches!(gating, Gating::BallotToken(_)) {
+            return Err(Error::InvalidGating);
+        }
+
+        let certificate = Certificate {
+            results: tally_map(&env),
+            result: Self::result(env.clone())?,
+            finalized_at: env.ledger().timestamp()
+        };
+
+        storage::store_certificate(&env, &certificate);
+        if let Gating::BallotToken(token) = gating {
+            if release != TokenRelease::Keep {
+                token::Client::new(&env, &token).retire(&(release == TokenRelease::Burn), &(release == TokenRelease::Unlock));
+            }
+        }
+
+        events::certified(&env, admin, certificate.clone());
+        Ok(certificate)
+    }
+
+    /// Certified outcome of a finalized ballot
+    pub fn certificate(env: Env) -> Result<Certificate, Error> {
+        storage::get_certificate(&env).ok_or(Error::BallotNotFinalized)
     }
 }
 
@@ -0,0 +1,31 @@
+use soroban_sdk::{xdr::ToXdr, Address, Bytes, BytesN, Env, Symbol, Vec};
+
+/// Leaf committed in the allowlist for the voter holding the ed25519 `voter_key` and `nullifier`
+pub fn leaf(env: &Env, voter_key: &BytesN<32>, nullifier: &BytesN<32>) -> BytesN<32> {
+    let mut data = Bytes::from_array(env, &voter_key.to_array());
+    data.append(&Bytes::from_array(env, &nullifier.to_array()));
+    env.crypto().sha256(&data)
+}
+
+/// Message the voter signs with its key, `xdr(ballot) || xdr(candidate) || nullifier`. It binds the candidate
+/// to the nullifier so that a relayer cannot cast the vote for someone else.
+pub fn vote_message(env: &Env, ballot: &Address, candidate: &Symbol, nullifier: &BytesN<32>) -> Bytes {
+    let mut message = ballot.clone().to_xdr(env);
+    message.append(&candidate.clone().to_xdr(env));
+    message.append(&Bytes::from_array(env, &nullifier.to_array()));
+    message
+}
+
+/// Checks `leaf` belongs to the tree with the given `root`. Pairs are hashed in sorted order, so the proof
+/// does not need to tell which side each sibling sits on.
+pub fn verify(env: &Env, root: &BytesN<32>, leaf: BytesN<32>, proof: &Vec<BytesN<32>>) -> bool {
+    let mut node = leaf;
+    for sibling in proof.iter() {
+        let (left, right) = if node <= sibling { (node, sibling) } else { (sibling, node) };
+        let mut data = Bytes::from_array(env, &left.to_array());
+        data.append(&Bytes::from_array(env, &right.to_array()));
+        node = env.crypto().sha256(&data);
+    }
+
+    node == *root
+}
@@ -1,21 +1,119 @@
-use soroban_sdk::{ contracttype, symbol_short, Address, Env, Symbol, Vec};
+use soroban_sdk::{ contracttype, symbol_short, Address, BytesN, Env, Map, String, Symbol, Vec};
 
-pub const VOTES: Symbol = symbol_short!("votes");
+use crate::tally::BallotResult;
+
+pub const TURNOUT: Symbol = symbol_short!("turnout");
+pub const ABSTAIN: Symbol = symbol_short!("abstain");
+pub const SEALED: Symbol = symbol_short!("sealed");
+pub const CAST: Symbol = symbol_short!("cast");
 pub const PARTIES: Symbol = symbol_short!("parties");
 pub const CONFIG: Symbol = symbol_short!("config");
 pub const TOKEN: Symbol = symbol_short!("token");
+pub const ADMIN: Symbol = symbol_short!("admin");
+pub const P_ADMIN: Symbol = symbol_short!("p_admin");
+pub const DELEGS: Symbol = symbol_short!("delegs");
+pub const FINAL: Symbol = symbol_short!("final");
+pub const RANKED: Symbol = symbol_short!("ranked");
+
+pub const DAY_IN_LEDGERS: u32 = 17280;
+pub const VOTE_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
+pub const VOTE_LIFETIME_THRESHOLD: u32 = VOTE_BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+pub const DEFAULT_DELEGATION_DEPTH: u32 = 8;
+pub const MAX_PAGE_SIZE: u32 = 50;
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
 #[contracttype]
-pub struct Config {
-    pub from: u64,
-    pub to: u64
+pub enum BallotMode {
+    #[default]
+    Plurality,
+    RankedChoice,
+    /// Voters approve any number of candidates up to the given maximum
+    Approval(u32),
+    /// Ranked ballots electing the given number of seats with single transferable vote
+    Stv(u32)
 }
 
-impl Default for Config {
-    fn default () -> Config {
-        Config { from: 0, to: 0 }
-    }
+/// Minimum participation for the ballot to be valid. Percentages are given in basis points of the BallotToken
+/// supply still live when the ballot ends, expired tokens are left out.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[contracttype]
+pub enum Quorum {
+    #[default]
+    Disabled,
+    Absolute(u32),
+    Percentage(u32)
+}
+
+/// Rules of the ballot. Quorum and threshold resolve the ballot once closed, the threshold being the minimum
+/// share, in basis points, the winner must get. `max_depth` limits the length of delegation chains, 0 applies
+/// `DEFAULT_DELEGATION_DEPTH`. A `reveal_period`, in seconds, turns the ballot into a commit-reveal one: votes
+/// are committed while the ballot is open and revealed during that period once it closes.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[contracttype]
+pub struct Rules {
+    pub quorum: Quorum,
+    pub threshold: u32,
+    pub max_depth: u32,
+    pub reveal_period: u64
+}
+
+/// How the voting weight of a SEP-41 holder follows its balance, counted in units of the minimum balance
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum Weighting {
+    Flat,
+    Linear,
+    Sqrt
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct TokenGate {
+    pub token: Address,
+    pub min_balance: i128,
+    pub weighting: Weighting
+}
+
+/// Who can vote. `BallotToken` holders get one vote each and can delegate it, any other SEP-41 token only
+/// requires a minimum balance and delegation is not available. `Merkle` holds the root of the voter
+/// commitments allowed to vote anonymously.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum Gating {
+    BallotToken(Address),
+    Sep41(TokenGate),
+    Merkle(BytesN<32>)
+}
+
+/// What happens to the BallotToken when the ballot is finalized. `CloseMinting` leaves the tokens with their
+/// holders, under the same transfer policy, and only stops any further minting. `Unlock` also stops minting
+/// and makes the tokens freely transferable.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum TokenRelease {
+    Keep,
+    Burn,
+    CloseMinting,
+    Unlock
+}
+
+/// Final tally and outcome written when the ballot is finalized. It never changes afterwards.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct Certificate {
+    pub results: Map<Symbol, u64>,
+    pub result: BallotResult,
+    pub finalized_at: u64
+}
+
+#[derive(Debug, Default)]
+#[contracttype]
+pub struct Config {
+    pub from: u64,
+    pub to: u64,
+    pub mode: BallotMode,
+    pub rules: Rules
 }
 
 #[contracttype]
@@ -23,6 +121,92 @@ pub enum VCounter {
     Counter(Symbol)
 }
 
+/// Hash of the candidate manifesto or any other reference document
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum Document {
+    None,
+    Hash(BytesN<32>)
+}
+
+/// Details the UI shows for a candidate
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct CandidateInfo {
+    pub name: String,
+    pub description: String,
+    pub url: String,
+    pub document: Document
+}
+
+/// Candidate record returned by the read API. Votes are only filled in once the ballot is closed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct Candidate {
+    pub id: Symbol,
+    pub info: CandidateInfo,
+    pub votes: u64
+}
+
+#[contracttype]
+pub enum CandidateKey {
+    Info(Symbol)
+}
+
+#[contracttype]
+pub enum VoterKey {
+    Vote(Address),
+    VoterAt(u32),
+    Delegate(Address),
+    Received(Address),
+    Height(Address),
+    DelegatorAt(u32),
+    Nullifier(BytesN<32>),
+    Escrow(Address),
+    Ranked(Vec<Symbol>),
+    RankedAt(u32)
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum Choice {
+    Single(Symbol),
+    Ranked(Vec<Symbol>),
+    Approval(Vec<Symbol>),
+    Abstain,
+    /// Commitment of a vote not revealed yet
+    Sealed(BytesN<32>)
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct VoteRecord {
+    pub choice: Choice,
+    pub weight: u64
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct VoterEntry {
+    pub voter: Address,
+    pub choice: Choice,
+    pub weight: u64
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct DelegationEntry {
+    pub delegator: Address,
+    pub delegate: Address
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct RankedBallot {
+    pub preferences: Vec<Symbol>,
+    pub weight: u64
+}
+
 pub fn get_candidates(env: &Env) -> Vec<Symbol> {
     let pts: Vec<Symbol> = env
         .storage()
@@ -45,40 +229,266 @@ pub fn store_party(env: &Env, p: &Symbol) -> bool {
     false
 }
 
-pub fn get_votes(env: &Env) -> Vec<Address>{
-    let vts: Vec<Address> = env
-        .storage()
-        .instance()
-        .get(&VOTES)
-        .unwrap_or(Vec::new(env))
-    ;
+pub fn remove_party(env: &Env, p: &Symbol) -> bool {
+    let mut pts: Vec<Symbol> = get_candidates(env);
+    if let Some(i) = pts.first_index_of(p) {
+        pts.remove(i);
+        env.storage().instance().set(&PARTIES, &pts);
+        return true;
+    }
+
+    false
+}
+
+/// Candidates registered without details get empty ones
+pub fn get_candidate_info(env: &Env, candidate: &Symbol) -> CandidateInfo {
+    let key = CandidateKey::Info(candidate.clone());
+    if let Some(info) = env.storage().persistent().get::<CandidateKey, CandidateInfo>(&key) {
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, VOTE_LIFETIME_THRESHOLD, VOTE_BUMP_AMOUNT);
+
+        return info;
+    }
+
+    let empty = String::from_str(env, "");
+    CandidateInfo {
+        name: empty.clone(),
+        description: empty.clone(),
+        url: empty,
+        document: Document::None
+    }
+}
+
+pub fn store_candidate_info(env: &Env, candidate: &Symbol, info: &CandidateInfo) {
+    let key = CandidateKey::Info(candidate.clone());
+    env.storage().persistent().set(&key, info);
+    env.storage().persistent().extend_ttl(&key, VOTE_LIFETIME_THRESHOLD, VOTE_BUMP_AMOUNT);
+}
 
-    vts
+pub fn remove_candidate_info(env: &Env, candidate: &Symbol) {
+    env.storage().persistent().remove(&CandidateKey::Info(candidate.clone()));
 }
 
-pub fn get_candidate_votes_count(env: &Env, candidate: &VCounter) -> u32 {
+#[allow(clippy::let_and_return)]
+pub fn get_candidate_votes_count(env: &Env, candidate: &VCounter) -> u64 {
     let total_votes = env.storage().instance().get(candidate).unwrap_or(0);
     total_votes
 }
 
-pub fn update_candidate_count(env: &Env, candidate: VCounter, count: u32) {
+pub fn update_candidate_count(env: &Env, candidate: VCounter, count: u64) {
     env.storage().instance().set(&candidate, &count);
 }
 
-pub fn update_votes(env: &Env, votes: Vec<Address>) {
-    env.storage().instance().set(&VOTES, &votes);
+/// Weight carried by every counted ballot, abstentions included
+pub fn get_cast_weight(env: &Env) -> u64 {
+    env.storage().instance().get(&CAST).unwrap_or(0)
 }
 
-pub fn store_config(env: &Env, ts_start: u64, ts_end: u64, token: Address) {
+pub fn store_cast_weight(env: &Env, weight: u64) {
+    env.storage().instance().set(&CAST, &weight);
+}
+
+pub fn get_turnout(env: &Env) -> u32 {
+    env.storage().instance().get(&TURNOUT).unwrap_or(0)
+}
+
+pub fn has_voted(env: &Env, voter: &Address) -> bool {
+    env.storage().persistent().has(&VoterKey::Vote(voter.clone()))
+}
+
+/// Stores the voter record in its own persistent entry and appends the voter to the turnout index, so that
+/// the cost of a vote does not depend on how many voters came before.
+pub fn store_vote(env: &Env, voter: &Address, record: &VoteRecord) {
+    let turnout = get_turnout(env);
+    let vote_key = VoterKey::Vote(voter.clone());
+    let index_key = VoterKey::VoterAt(turnout);
+
+    env.storage().persistent().set(&vote_key, record);
+    env.storage().persistent().set(&index_key, voter);
+    env.storage().persistent().extend_ttl(&vote_key, VOTE_LIFETIME_THRESHOLD, VOTE_BUMP_AMOUNT);
+    env.storage().persistent().extend_ttl(&index_key, VOTE_LIFETIME_THRESHOLD, VOTE_BUMP_AMOUNT);
+    env.storage().instance().set(&TURNOUT, &(turnout + 1));
+}
+
+pub fn is_nullifier_spent(env: &Env, nullifier: &BytesN<32>) -> bool {
+    env.storage().persistent().has(&VoterKey::Nullifier(nullifier.clone()))
+}
+
+/// Marks the nullifier as spent and counts the anonymous vote in the turnout. No voter address is recorded.
+pub fn spend_nullifier(env: &Env, nullifier: &BytesN<32>) {
+    let key = VoterKey::Nullifier(nullifier.clone());
+    env.storage().persistent().set(&key, &true);
+    env.storage().persistent().extend_ttl(&key, VOTE_LIFETIME_THRESHOLD, VOTE_BUMP_AMOUNT);
+    env.storage().instance().set(&TURNOUT, &(get_turnout(env) + 1));
+}
+
+/// Replaces the record of a voter who already voted, leaving the turnout index untouched.
+pub fn update_vote(env: &Env, voter: &Address, record: &VoteRecord) {
+    let vote_key = VoterKey::Vote(voter.clone());
+    env.storage().persistent().set(&vote_key, record);
+    env.storage().persistent().extend_ttl(&vote_key, VOTE_LIFETIME_THRESHOLD, VOTE_BUMP_AMOUNT);
+}
+
+pub fn get_vote(env: &Env, voter: &Address) -> Option<VoteRecord> {
+    let key = VoterKey::Vote(voter.clone());
+    if let Some(r) = env.storage().persistent().get::<VoterKey, VoteRecord>(&key) {
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, VOTE_LIFETIME_THRESHOLD, VOTE_BUMP_AMOUNT);
+
+        return Some(r);
+    }
+
+    None
+}
+
+pub fn get_voter_at(env: &Env, index: u32) -> Address {
+    let key = VoterKey::VoterAt(index);
+    let voter: Address = env.storage().persistent().get(&key).unwrap();
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, VOTE_LIFETIME_THRESHOLD, VOTE_BUMP_AMOUNT);
+
+    voter
+}
+
+pub fn get_delegate(env: &Env, voter: &Address) -> Option<Address> {
+    let key = VoterKey::Delegate(voter.clone());
+    if let Some(d) = env.storage().persistent().get::<VoterKey, Address>(&key) {
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, VOTE_LIFETIME_THRESHOLD, VOTE_BUMP_AMOUNT);
+
+        return Some(d);
+    }
+
+    None
+}
+
+pub fn get_delegations_count(env: &Env) -> u32 {
+    env.storage().instance().get(&DELEGS).unwrap_or(0)
+}
+
+/// Stores the delegation and appends the delegator to the delegations index
+pub fn store_delegate(env: &Env, voter: &Address, delegate: &Address) {
+    let count = get_delegations_count(env);
+    let key = VoterKey::Delegate(voter.clone());
+    let index_key = VoterKey::DelegatorAt(count);
+
+    env.storage().persistent().set(&key, delegate);
+    env.storage().persistent().set(&index_key, voter);
+    env.storage().persistent().extend_ttl(&key, VOTE_LIFETIME_THRESHOLD, VOTE_BUMP_AMOUNT);
+    env.storage().persistent().extend_ttl(&index_key, VOTE_LIFETIME_THRESHOLD, VOTE_BUMP_AMOUNT);
+    env.storage().instance().set(&DELEGS, &(count + 1));
+}
+
+pub fn get_delegator_at(env: &Env, index: u32) -> Address {
+    let key = VoterKey::DelegatorAt(index);
+    let delegator: Address = env.storage().persistent().get(&key).unwrap();
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, VOTE_LIFETIME_THRESHOLD, VOTE_BUMP_AMOUNT);
+
+    delegator
+}
+
+/// Weight delegated to the voter by delegators who have not voted themselves.
+pub fn get_received(env: &Env, voter: &Address) -> u64 {
+    env.storage().persistent().get(&VoterKey::Received(voter.clone())).unwrap_or(0)
+}
+
+pub fn store_received(env: &Env, voter: &Address, weight: u64) {
+    let key = VoterKey::Received(voter.clone());
+    env.storage().persistent().set(&key, &weight);
+    env.storage().persistent().extend_ttl(&key, VOTE_LIFETIME_THRESHOLD, VOTE_BUMP_AMOUNT);
+}
+
+/// Length of the longest delegation chain ending at the voter.
+pub fn get_height(env: &Env, voter: &Address) -> u32 {
+    env.storage().persistent().get(&VoterKey::Height(voter.clone())).unwrap_or(0)
+}
+
+pub fn store_height(env: &Env, voter: &Address, height: u32) {
+    let key = VoterKey::Height(voter.clone());
+    env.storage().persistent().set(&key, &height);
+    env.storage().persistent().extend_ttl(&key, VOTE_LIFETIME_THRESHOLD, VOTE_BUMP_AMOUNT);
+}
+
+/// Number of distinct preference lists ranked ballots have been cast with
+pub fn get_ranked_count(env: &Env) -> u32 {
+    env.storage().instance().get(&RANKED).unwrap_or(0)
+}
+
+/// Ranked ballots grouped by preference list, each list in its own entry
+pub fn get_ranked_at(env: &Env, index: u32) -> RankedBallot {
+    let key = VoterKey::RankedAt(index);
+    let ballot: RankedBallot = env.storage().persistent().get(&key).unwrap();
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, VOTE_LIFETIME_THRESHOLD, VOTE_BUMP_AMOUNT);
+
+    ballot
+}
+
+fn get_ranked_index(env: &Env, preferences: &Vec<Symbol>) -> Option<u32> {
+    env.storage().persistent().get(&VoterKey::Ranked(preferences.clone()))
+}
+
+pub fn get_ranked_weight(env: &Env, preferences: &Vec<Symbol>) -> u64 {
+    match get_ranked_index(env, preferences) {
+        Some(index) => get_ranked_at(env, index).weight,
+        None => 0
+    }
+}
+
+/// Stores the weight of the preference list, appending the list to the ranked index the first time it is used
+pub fn store_ranked_weight(env: &Env, preferences: &Vec<Symbol>, weight: u64) {
+    let index = match get_ranked_index(env, preferences) {
+        Some(index) => index,
+        None => {
+            let count = get_ranked_count(env);
+            let key = VoterKey::Ranked(preferences.clone());
+            env.storage().persistent().set(&key, &count);
+            env.storage().persistent().extend_ttl(&key, VOTE_LIFETIME_THRESHOLD, VOTE_BUMP_AMOUNT);
+            env.storage().instance().set(&RANKED, &(count + 1));
+            count
+        }
+    };
+
+    let key = VoterKey::RankedAt(index);
+    env.storage().persistent().set(&key, &RankedBallot { preferences: preferences.clone(), weight });
+    env.storage().persistent().extend_ttl(&key, VOTE_LIFETIME_THRESHOLD, VOTE_BUMP_AMOUNT);
+}
+
+/// SEP-41 balance the ballot holds for the voter until the voting window closes
+pub fn get_escrow(env: &Env, voter: &Address) -> i128 {
+    env.storage().persistent().get(&VoterKey::Escrow(voter.clone())).unwrap_or(0)
+}
+
+pub fn store_escrow(env: &Env, voter: &Address, amount: i128) {
+    let key = VoterKey::Escrow(voter.clone());
+    env.storage().persistent().set(&key, &amount);
+    env.storage().persistent().extend_ttl(&key, VOTE_LIFETIME_THRESHOLD, VOTE_BUMP_AMOUNT);
+}
+
+pub fn remove_escrow(env: &Env, voter: &Address) {
+    env.storage().persistent().remove(&VoterKey::Escrow(voter.clone()));
+}
+
+pub fn store_config(env: &Env, ts_start: u64, ts_end: u64, mode: BallotMode, rules: Rules, gating: Gating) {
     let cfg = Config {
         from: ts_start,
-        to: ts_end
+        to: ts_end,
+        mode,
+        rules
     };
 
     env.storage().instance().set(&CONFIG, &cfg);
-    env.storage().instance().set(&TOKEN, &token)
+    env.storage().instance().set(&TOKEN, &gating)
 }
 
+#[allow(clippy::let_and_return, clippy::unwrap_or_default)]
 pub fn get_config(env: &Env) -> Config {
     let cfg = env
         .storage()
@@ -90,7 +500,35 @@ pub fn get_config(env: &Env) -> Config {
     cfg
 }
 
-pub fn get_token(env: &Env) -> Address {
-    let token: Address = env.storage().instance().get(&TOKEN).unwrap();
-    token
-}
\ No newline at end of file
+pub fn get_admin(env: &Env) -> Option<Address> {
+    env.storage().instance().get(&ADMIN)
+}
+
+pub fn store_admin(env: &Env, admin: &Address) {
+    env.storage().instance().set(&ADMIN, admin);
+}
+
+pub fn get_pending_admin(env: &Env) -> Option<Address> {
+    env.storage().instance().get(&P_ADMIN)
+}
+
+pub fn store_pending_admin(env: &Env, admin: &Address) {
+    env.storage().instance().set(&P_ADMIN, admin);
+}
+
+pub fn remove_pending_admin(env: &Env) {
+    env.storage().instance().remove(&P_ADMIN);
+}
+
+pub fn get_certificate(env: &Env) -> Option<Certificate> {
+    env.storage().instance().get(&FINAL)
+}
+
+pub fn store_certificate(env: &Env, certificate: &Certificate) {
+    env.storage().instance().set(&FINAL, certificate);
+}
+
+pub fn get_gating(env: &Env) -> Gating {
+    let gating: Gating = env.storage().instance().get(&TOKEN).unwrap();
+    gating
+}
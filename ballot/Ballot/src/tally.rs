@@ -0,0 +1,321 @@
+use soroban_sdk::{contracttype, Env, Map, Symbol, Vec};
+
+use crate::storage::{Quorum, RankedBallot};
+
+pub const MAX_BPS: u32 = 10_000;
+/// STV counts are kept in fixed point, in units of 1 / STV_SCALE votes
+pub const STV_SCALE: u128 = 100_000;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct RankedRound {
+    pub counts: Map<Symbol, u64>,
+    pub eliminated: Vec<Symbol>
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum RankedOutcome {
+    Winner(Symbol),
+    Tie(Vec<Symbol>),
+    NoVotes
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct RankedTally {
+    pub rounds: Vec<RankedRound>,
+    pub outcome: RankedOutcome
+}
+
+/// Counts of an STV round in units of 1 / STV_SCALE votes, with the candidates elected or excluded at the
+/// end of the round and the value of the ballots left without any continuing preference.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct StvRound {
+    pub counts: Map<Symbol, u128>,
+    pub elected: Vec<Symbol>,
+    pub excluded: Vec<Symbol>,
+    pub exhausted: u128
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct StvTally {
+    pub quota: u128,
+    pub rounds: Vec<StvRound>,
+    pub elected: Vec<Symbol>
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum BallotResult {
+    Winner(Symbol),
+    NoQuorum,
+    Tie(Vec<Symbol>),
+    ThresholdNotMet,
+    /// Candidates elected in a multi-seat ballot, in the order they were elected
+    Elected(Vec<Symbol>)
+}
+
+pub fn is_quorum_reached(quorum: &Quorum, cast: u64, supply: u32) -> bool {
+    match quorum {
+        Quorum::Disabled => true,
+        Quorum::Absolute(n) => cast >= *n as u64,
+        Quorum::Percentage(bps) => (cast as u128) * (MAX_BPS as u128) >= (*bps as u128) * (supply as u128)
+    }
+}
+
+/// Resolves the ballot from the final counts. The threshold is the share of the `total` weight, in basis
+/// points, the most voted candidate needs to win.
+pub fn plurality(env: &Env, counts: &Map<Symbol, u64>, total: u64, threshold: u32) -> BallotResult {
+    let mut leaders: Vec<Symbol> = Vec::new(env);
+    let mut max: u64 = 0;
+    for (c, n) in counts.iter() {
+        if n > max {
+            max = n;
+            leaders = Vec::new(env);
+        }
+
+        if n == max {
+            leaders.push_back(c);
+        }
+    }
+
+    if leaders.len() > 1 {
+        return BallotResult::Tie(leaders);
+    }
+
+    if (max as u128) * (MAX_BPS as u128) < (threshold as u128) * (total as u128) {
+        return BallotResult::ThresholdNotMet;
+    }
+
+    BallotResult::Winner(leaders.first().unwrap())
+}
+
+/// Runs instant-runoff rounds over the ranked ballots. Every round each ballot counts for its highest
+/// ranked candidate still in the race. A candidate holding more than half of the non exhausted weight wins,
+/// otherwise all the candidates sharing the lowest count are eliminated. When every remaining candidate is
+/// tied the ballot ends up in a tie between them.
+pub fn instant_runoff(env: &Env, candidates: Vec<Symbol>, ballots: &Vec<RankedBallot>) -> RankedTally {
+    let mut active: Vec<Symbol> = candidates;
+    let mut rounds: Vec<RankedRound> = Vec::new(env);
+    let mut outcome = RankedOutcome::NoVotes;
+
+    while !active.is_empty() {
+        let mut counts: Map<Symbol, u64> = Map::new(env);
+        for c in active.iter() {
+            counts.set(c, 0);
+        }
+
+        let mut total: u64 = 0;
+        for ballot in ballots.iter() {
+            if let Some(pref) = ballot.preferences.iter().find(|p| active.contains(p)) {
+                counts.set(pref.clone(), counts.get(pref).unwrap_or(0) + ballot.weight);
+                total += ballot.weight;
+            }
+        }
+
+        if total == 0 {
+            rounds.push_back(RankedRound { counts, eliminated: Vec::new(env) });
+            break;
+        }
+
+        let mut leader: Symbol = active.first().unwrap();
+        let mut max: u64 = 0;
+        let mut min: u64 = u64::MAX;
+        for (c, n) in counts.iter() {
+            if n > max {
+                max = n;
+                leader = c;
+            }
+
+            if n < min {
+                min = n;
+            }
+        }
+
+        if (max as u128) * 2 > total as u128 {
+            outcome = RankedOutcome::Winner(leader);
+            rounds.push_back(RankedRound { counts, eliminated: Vec::new(env) });
+            break;
+        }
+
+        let mut eliminated: Vec<Symbol> = Vec::new(env);
+        for (c, n) in counts.iter() {
+            if n == min {
+                eliminated.push_back(c);
+            }
+        }
+
+        if eliminated.len() == active.len() {
+            // All the remaining candidates are tied, nobody can be eliminated
+            outcome = RankedOutcome::Tie(eliminated);
+            rounds.push_back(RankedRound { counts, eliminated: Vec::new(env) });
+            break;
+        }
+
+        let mut remaining: Vec<Symbol> = Vec::new(env);
+        for c in active.iter() {
+            if !eliminated.contains(&c) {
+                remaining.push_back(c);
+            }
+        }
+
+        active = remaining;
+        rounds.push_back(RankedRound { counts, eliminated });
+    }
+
+    RankedTally { rounds, outcome }
+}
+
+/// Runs a single transferable vote count for `seats` seats with the Droop quota, `votes / (seats + 1) + 1`.
+/// Every round each ballot counts, at its current value, for its highest ranked continuing candidate.
+/// Candidates reaching the quota are elected and the ballots counting for them carry on at
+/// `value * surplus / total`, truncated to STV_SCALE, so the count is deterministic. When nobody reaches
+/// the quota the lowest candidate is excluded and its ballots carry on at their value. Once there are no
+/// more continuing candidates than seats left they are all elected.
+pub fn single_transferable_vote(env: &Env, candidates: Vec<Symbol>, seats: u32, ballots: &Vec<RankedBallot>) -> StvTally {
+    let mut values: Vec<u128> = Vec::new(env);
+    let mut votes: u128 = 0;
+    for ballot in ballots.iter() {
+        values.push_back(ballot.weight as u128 * STV_SCALE);
+        votes += ballot.weight as u128;
+    }
+
+    let quota = (votes / (seats as u128 + 1) + 1) * STV_SCALE;
+    let mut hopeful: Vec<Symbol> = candidates.clone();
+    let mut elected: Vec<Symbol> = Vec::new(env);
+    let mut rounds: Vec<StvRound> = Vec::new(env);
+
+    while elected.len() < seats && !hopeful.is_empty() {
+        let mut counts: Map<Symbol, u128> = Map::new(env);
+        for c in hopeful.iter() {
+            counts.set(c, 0);
+        }
+
+        let mut exhausted: u128 = 0;
+        for (i, ballot) in ballots.iter().enumerate() {
+            let value = values.get(i as u32).unwrap();
+            match ballot.preferences.iter().find(|p| hopeful.contains(p)) {
+                Some(pref) => counts.set(pref.clone(), counts.get(pref).unwrap() + value),
+                None => exhausted += value
+            }
+        }
+
+        let seats_left = seats - elected.len();
+        let mut round_elected: Vec<Symbol> = Vec::new(env);
+        let mut excluded: Vec<Symbol> = Vec::new(env);
+        if hopeful.len() <= seats_left {
+            round_elected = by_count(env, &hopeful, &counts, &rounds, &candidates);
+        } else {
+            let mut reached: Vec<Symbol> = Vec::new(env);
+            for (c, n) in counts.iter() {
+                if n >= quota {
+                    reached.push_back(c);
+                }
+            }
+
+            if reached.is_empty() {
+                let ranked = by_count(env, &hopeful, &counts, &rounds, &candidates);
+                excluded.push_back(ranked.last().unwrap());
+            } else {
+                for c in by_count(env, &reached, &counts, &rounds, &candidates).iter().take(seats_left as usize) {
+                    transfer_surplus(&mut values, ballots, &hopeful, &c, counts.get(c.clone()).unwrap(), quota);
+                    round_elected.push_back(c);
+                }
+            }
+        }
+
+        let mut remaining: Vec<Symbol> = Vec::new(env);
+        for c in hopeful.iter() {
+            if !round_elected.contains(&c) && !excluded.contains(&c) {
+                remaining.push_back(c);
+            }
+        }
+
+        hopeful = remaining;
+        elected.append(&round_elected);
+        rounds.push_back(StvRound { counts, elected: round_elected, excluded, exhausted });
+    }
+
+    StvTally { quota, rounds, elected }
+}
+
+/// Scales down the ballots counting for the elected candidate so that only its surplus carries on
+fn transfer_surplus(values: &mut Vec<u128>, ballots: &Vec<RankedBallot>, hopeful: &Vec<Symbol>, candidate: &Symbol, total: u128, quota: u128) {
+    let surplus = total - quota;
+    for (i, ballot) in ballots.iter().enumerate() {
+        if ballot.preferences.iter().find(|p| hopeful.contains(p)).as_ref() == Some(candidate) {
+            let value = values.get(i as u32).unwrap();
+            values.set(i as u32, mul_div(value, surplus, total));
+        }
+    }
+}
+
+/// `a * b / c` rounded down, for `a * b / c` below 2^128 and `c` below 2^127. The product is kept in 256
+/// bits, so very large electorates do not overflow the transfer.
+fn mul_div(a: u128, b: u128, c: u128) -> u128 {
+    if let Some(product) = a.checked_mul(b) {
+        return product / c;
+    }
+
+    let mask = u64::MAX as u128;
+    let (a1, a0) = (a >> 64, a & mask);
+    let (b1, b0) = (b >> 64, b & mask);
+    let (p00, p01, p10, p11) = (a0 * b0, a0 * b1, a1 * b0, a1 * b1);
+    let mid = (p00 >> 64) + (p01 & mask) + (p10 & mask);
+    let lo = (p00 & mask) | (mid << 64);
+    let hi = p11 + (p01 >> 64) + (p10 >> 64) + (mid >> 64);
+
+    // Long division of hi:lo by c, one bit at a time
+    let mut quotient: u128 = 0;
+    let mut rest: u128 = 0;
+    for i in (0..256).rev() {
+        let bit = if i >= 128 { (hi >> (i - 128)) & 1 } else { (lo >> i) & 1 };
+        rest = (rest << 1) | bit;
+        if rest >= c {
+            rest -= c;
+            if i < 128 {
+                quotient |= 1 << i;
+            }
+        }
+    }
+
+    quotient
+}
+
+/// Sorts the candidates from the highest to the lowest count. Ties are broken by the counts of the
+/// previous rounds, latest first, and then by registration order.
+fn by_count(env: &Env, list: &Vec<Symbol>, counts: &Map<Symbol, u128>, rounds: &Vec<StvRound>, candidates: &Vec<Symbol>) -> Vec<Symbol> {
+    let mut sorted: Vec<Symbol> = Vec::new(env);
+    for c in list.iter() {
+        let mut at = sorted.len();
+        for (i, s) in sorted.iter().enumerate() {
+            if ranks_higher(&c, &s, counts, rounds, candidates) {
+                at = i as u32;
+                break;
+            }
+        }
+
+        sorted.insert(at, c);
+    }
+
+    sorted
+}
+
+fn ranks_higher(a: &Symbol, b: &Symbol, counts: &Map<Symbol, u128>, rounds: &Vec<StvRound>, candidates: &Vec<Symbol>) -> bool {
+    let (x, y) = (counts.get(a.clone()).unwrap(), counts.get(b.clone()).unwrap());
+    if x != y {
+        return x > y;
+    }
+
+    for round in rounds.iter().rev() {
+        let (x, y) = (round.counts.get(a.clone()).unwrap(), round.counts.get(b.clone()).unwrap());
+        if x != y {
+            return x > y;
+        }
+    }
+
+    candidates.first_index_of(a) < candidates.first_index_of(b)
+}
@@ -1,7 +1,8 @@
 #![cfg(test)]
 
-use super::{token, Ballot, BallotClient};
-use soroban_sdk::{symbol_short, testutils::{Address as _, Ledger}, Address, Env, Vec};
+use super::{token, Ballot, BallotClient, BallotMode, Candidate, CandidateInfo, Choice, DelegationEntry, Error, Gating, Quorum, Rules, VoterEntry, storage::{Certificate, Document, RankedBallot, TokenGate, TokenRelease, Weighting}, tally::{single_transferable_vote, BallotResult, RankedOutcome, StvRound, STV_SCALE}};
+use ed25519_dalek::{Signer, SigningKey};
+use soroban_sdk::{symbol_short, token::{StellarAssetClient, TokenClient}, xdr::ToXdr, testutils::{Address as _, Events, Ledger}, vec, Address, Bytes, BytesN, Env, IntoVal, Map, String, Symbol, Val, Vec};
 
 struct TestData<'a> {
     admin: Address,
@@ -9,19 +10,24 @@ struct TestData<'a> {
     token: token::Client<'a>
 }
 
-fn get_test_data(env: &Env, num_voters: u8) -> TestData {
-    let admin = Address::generate(&env);
-    let mut voters = Vec::new(&env);
+fn get_test_data(env: &Env, num_voters: u32) -> TestData<'_> {
+    let admin = Address::generate(env);
+    let mut voters = Vec::new(env);
     let token_address = env.register_contract_wasm(None, token::WASM);
-    let token = token::Client::new(&env, &token_address);
-    token.initialize(&admin);
+    let token = token::Client::new(env, &token_address);
+    token.initialize(&admin, &token::TransferPolicy::Transferable);
 
+    // Tokens are handed out before any ballot opens
+    let now = env.ledger().timestamp();
+    env.ledger().with_mut(|l| {l.timestamp = 0;});
     for _i in 0..num_voters {
-        let addr: Address = Address::generate(&env);
-        token.mint(&addr);
+        let addr: Address = Address::generate(env);
+        token.mint(&addr, &None);
         voters.push_back(addr);
     }
 
+    env.ledger().with_mut(|l| {l.timestamp = now;});
+
     TestData {
         admin,
         voters,
@@ -29,6 +35,28 @@ fn get_test_data(env: &Env, num_voters: u8) -> TestData {
     }
 }
 
+/// Configures the ballot and registers the candidates right before the voting window opens
+fn open_ballot(env: &Env, client: &BallotClient, test_data: &TestData, ts_start: u64, ts_end: u64, mode: BallotMode) {
+    open_ballot_with_rules(env, client, test_data, ts_start, ts_end, mode, Rules::default());
+}
+
+fn open_ballot_with_rules(env: &Env, client: &BallotClient, test_data: &TestData, ts_start: u64, ts_end: u64, mode: BallotMode, rules: Rules) {
+    let now = env.ledger().timestamp();
+    env.ledger().with_mut(|l| {l.timestamp = ts_start - 1;});
+    client.configure(&test_data.admin, &Gating::BallotToken(test_data.token.address.clone()), &ts_start, &ts_end, &mode, &rules);
+    client.register_candidates(&test_data.admin, &candidates(env));
+    env.ledger().with_mut(|l| {l.timestamp = now;});
+}
+
+fn last_event(env: &Env) -> Vec<(Address, Vec<Val>, Val)> {
+    let events = env.events().all();
+    events.slice(events.len() - 1..)
+}
+
+fn candidates(env: &Env) -> Vec<Symbol> {
+    vec![env, symbol_short!("Laborist"), symbol_short!("Conserv"), symbol_short!("Liberal")]
+}
+
 #[test]
 fn vote_test() {
     let env = Env::default();
@@ -39,23 +67,26 @@ fn vote_test() {
     let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
     let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
 
-    client.configure(&test_data.admin, &test_data.token.address, &ts_start, &ts_end);
-    assert_eq!(client.vote(&test_data.voters.get(0).unwrap(), &symbol_short!("Laborist")), true);
-    assert_eq!(client.vote(&test_data.voters.get(1).unwrap(), &symbol_short!("Conserv")), true);
-    assert_eq!(client.vote(&test_data.voters.get(2).unwrap(), &symbol_short!("Conserv")), true);
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::Plurality);
+    assert!(client.vote(&test_data.voters.get(0).unwrap(), &symbol_short!("Laborist")));
+    assert!(client.vote(&test_data.voters.get(1).unwrap(), &symbol_short!("Conserv")));
+    assert!(client.vote(&test_data.voters.get(2).unwrap(), &symbol_short!("Conserv")));
 
     let count = client.count(&test_data.admin);
 
-    assert_eq!(count.get(symbol_short!("Laborist")).unwrap(), 1);
-    assert_eq!(count.get(symbol_short!("Conserv")).unwrap(), 2);
+    assert_eq!(count.len(), 1);
+    assert_eq!(count.get(symbol_short!("turnout")).unwrap(), 3);
 
     client.delegate(&test_data.voters.get(3).unwrap(), &test_data.voters.get(4).unwrap());
-    assert_eq!(client.vote(&&test_data.voters.get(4).unwrap(), &symbol_short!("Conserv")), true);
+    assert!(client.vote(&test_data.voters.get(4).unwrap(), &symbol_short!("Conserv")));
 
-    let count = client.count(&test_data.admin);
+    env.ledger().with_mut(|l| {l.timestamp = ts_end + 1;});
+    let count = client.results();
 
     assert_eq!(count.get(symbol_short!("Laborist")).unwrap(), 1);
     assert_eq!(count.get(symbol_short!("Conserv")).unwrap(),4);
+    assert_eq!(count.get(symbol_short!("turnout")).unwrap(), 4);
+    assert_eq!(client.count(&test_data.admin), count);
 
 }
 
@@ -69,7 +100,7 @@ fn vote_out_of_dates_test() {
     let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
     let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
 
-    client.configure(&test_data.admin, &test_data.token.address, &ts_start, &ts_end);
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::Plurality);
     client.vote(&test_data.voters.get(0).unwrap(), &symbol_short!("Laborist"));
 }
 
@@ -85,7 +116,7 @@ fn vote_test_already_voted() {
     let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
 
     let voter = test_data.voters.get(0).unwrap();
-    client.configure(&test_data.admin, &test_data.token.address, &ts_start, &ts_end);
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::Plurality);
     client.vote(&voter, &symbol_short!("Laborist"));
     client.vote(&voter, &symbol_short!("Laborist"));
 }
@@ -103,7 +134,7 @@ fn voter_cannot_delegate_since_does_not_hold_token() {
     let voter1 = Address::generate(&env);
     let voter2 = Address::generate(&env);
 
-    client.configure(&test_data.admin, &test_data.token.address, &ts_start, &ts_end);
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::Plurality);
     client.delegate(&voter1, &voter2);
 }
 
@@ -112,16 +143,15 @@ fn voter_cannot_delegate_since_does_not_hold_token() {
 fn voter_cannot_delegate_since_target_does_not_hold_token() {
     let env = Env::default();
     let client = create_client(&env);
-    let test_data = get_test_data(&env, 0);
+    let test_data = get_test_data(&env, 1);
     env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
 
     let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
     let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
-    let voter1 = Address::generate(&env);
+    let voter1 = test_data.voters.get(0).unwrap();
     let voter2 = Address::generate(&env);
     
-    test_data.token.mint(&voter1);
-    client.configure(&test_data.admin, &test_data.token.address, &ts_start, &ts_end);
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::Plurality);
     client.delegate(&voter1, &voter2);
 }
 
@@ -138,7 +168,7 @@ fn voter_cannot_delegate_since_it_has_voted() {
     let voter1 = test_data.voters.get(0).unwrap();
     let voter2 = test_data.voters.get(1).unwrap();
 
-    client.configure(&test_data.admin, &test_data.token.address, &ts_start, &ts_end);
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::Plurality);
     client.vote(&voter1, &symbol_short!("Laborist"));
     client.delegate(&voter1, &voter2);
 }
@@ -156,7 +186,7 @@ fn vote_cannot_be_delegated_since_target_has_voted() {
     let voter1 = test_data.voters.get(0).unwrap();
     let voter2 = test_data.voters.get(1).unwrap();
 
-    client.configure(&test_data.admin, &test_data.token.address, &ts_start, &ts_end);
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::Plurality);
     client.vote(&voter2, &symbol_short!("Laborist"));
     client.delegate(&voter1, &voter2);
 }
@@ -174,14 +204,13 @@ fn voter_has_already_delegated_its_vote() {
     let voter1 = test_data.voters.get(0).unwrap();
     let voter2 = test_data.voters.get(1).unwrap();
 
-    client.configure(&test_data.admin, &test_data.token.address, &ts_start, &ts_end);
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::Plurality);
     client.delegate(&voter1, &voter2);
     client.delegate(&voter1, &voter2);
 }
 
 #[test]
-#[should_panic(expected = "HostError: Error(Contract, #3)")]
-fn voter_target_has_already_a_delegated_vote() {
+fn delegate_to_voter_with_delegated_vote_test() {
     let env = Env::default();
     let client = create_client(&env);
     let test_data = get_test_data(&env, 3);
@@ -193,15 +222,2126 @@ fn voter_target_has_already_a_delegated_vote() {
     let voter2 = test_data.voters.get(1).unwrap();
     let voter3 = test_data.voters.get(2).unwrap();
 
-    client.configure(&test_data.admin, &test_data.token.address, &ts_start, &ts_end);
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::Plurality);
     client.delegate(&voter1, &voter2);
     client.delegate(&voter3, &voter2);
+    client.vote(&voter2, &symbol_short!("Laborist"));
+
+    env.ledger().with_mut(|l| {l.timestamp = ts_end + 1;});
+    assert_eq!(client.results().get(symbol_short!("Laborist")).unwrap(), 3);
+}
+
+#[test]
+fn delegation_chain_test() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 4);
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+    let (a, b, c, d) = (test_data.voters.get(0).unwrap(), test_data.voters.get(1).unwrap(), test_data.voters.get(2).unwrap(), test_data.voters.get(3).unwrap());
+
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::Plurality);
+    client.delegate(&a, &b);
+    client.delegate(&b, &c);
+    client.vote(&c, &symbol_short!("Laborist"));
+
+    // D joins the chain once its last delegate has already voted
+    client.delegate(&d, &a);
+
+    env.ledger().with_mut(|l| {l.timestamp = ts_end + 1;});
+    let count = client.results();
+    assert_eq!(count.get(symbol_short!("Laborist")).unwrap(), 4);
+    assert_eq!(count.get(symbol_short!("turnout")).unwrap(), 1);
+}
+
+#[test]
+fn intermediate_delegate_votes_test() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 4);
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+    let (a, b, c, d) = (test_data.voters.get(0).unwrap(), test_data.voters.get(1).unwrap(), test_data.voters.get(2).unwrap(), test_data.voters.get(3).unwrap());
+
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::Plurality);
+    client.delegate(&a, &b);
+    client.delegate(&b, &c);
+    client.delegate(&d, &c);
+    client.vote(&c, &symbol_short!("Laborist"));
+
+    // B overrides its delegation and takes A's weight with it
+    client.vote(&b, &symbol_short!("Conserv"));
+
+    env.ledger().with_mut(|l| {l.timestamp = ts_end + 1;});
+    let count = client.results();
+    assert_eq!(count.get(symbol_short!("Laborist")).unwrap(), 2);
+    assert_eq!(count.get(symbol_short!("Conserv")).unwrap(), 2);
+}
+
+#[test]
+fn intermediate_delegate_votes_first_test() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 3);
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+    let (a, b, c) = (test_data.voters.get(0).unwrap(), test_data.voters.get(1).unwrap(), test_data.voters.get(2).unwrap());
+
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::RankedChoice);
+    client.delegate(&a, &b);
+    client.delegate(&b, &c);
+    client.vote_ranked(&b, &vec![&env, symbol_short!("Conserv")]);
+    client.vote_ranked(&c, &vec![&env, symbol_short!("Laborist")]);
+
+    env.ledger().with_mut(|l| {l.timestamp = ts_end + 1;});
+    let tally = client.tally_ranked(&test_data.admin);
+    let first_round = tally.rounds.get(0).unwrap();
+    assert_eq!(first_round.counts.get(symbol_short!("Conserv")).unwrap(), 2);
+    assert_eq!(first_round.counts.get(symbol_short!("Laborist")).unwrap(), 1);
+    assert_eq!(tally.outcome, RankedOutcome::Winner(symbol_short!("Conserv")));
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #19)")]
+fn delegation_cycle_test() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 3);
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+    let (a, b, c) = (test_data.voters.get(0).unwrap(), test_data.voters.get(1).unwrap(), test_data.voters.get(2).unwrap());
+
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::Plurality);
+    client.delegate(&a, &b);
+    client.delegate(&b, &c);
+    client.delegate(&c, &a);
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #20)")]
+fn delegation_too_deep_test() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 4);
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+    let (a, b, c, d) = (test_data.voters.get(0).unwrap(), test_data.voters.get(1).unwrap(), test_data.voters.get(2).unwrap(), test_data.voters.get(3).unwrap());
+
+    open_ballot_with_rules(&env, &client, &test_data, ts_start, ts_end, BallotMode::Plurality, Rules { quorum: Quorum::Disabled, threshold: 0, max_depth: 2, reveal_period: 0 });
+    client.delegate(&a, &b);
+    client.delegate(&c, &d);
+
+    // Joining both chains would make a chain of three delegations
+    client.delegate(&b, &c);
+}
+
+#[test]
+fn ranked_vote_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 5);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    let (lab, con, lib) = (symbol_short!("Laborist"), symbol_short!("Conserv"), symbol_short!("Liberal"));
+
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::RankedChoice);
+    client.vote_ranked(&test_data.voters.get(0).unwrap(), &vec![&env, lab.clone(), lib.clone()]);
+    client.vote_ranked(&test_data.voters.get(1).unwrap(), &vec![&env, con.clone(), lib.clone()]);
+    client.vote_ranked(&test_data.voters.get(2).unwrap(), &vec![&env, lib.clone(), lab.clone()]);
+    client.vote_ranked(&test_data.voters.get(3).unwrap(), &vec![&env, lab.clone()]);
+    client.vote_ranked(&test_data.voters.get(4).unwrap(), &vec![&env, con.clone(), lab.clone()]);
+
+    env.ledger().with_mut(|l| {l.timestamp = ts_end + 1;});
+    let count = client.count(&test_data.admin);
+    assert_eq!(count.get(lab.clone()).unwrap(), 2);
+    assert_eq!(count.get(con.clone()).unwrap(), 2);
+    assert_eq!(count.get(lib.clone()).unwrap(), 1);
+
+    let tally = client.tally_ranked(&test_data.admin);
+    assert_eq!(tally.rounds.len(), 2);
+    assert_eq!(tally.rounds.get(0).unwrap().eliminated, vec![&env, lib.clone()]);
+
+    let last_round = tally.rounds.get(1).unwrap();
+    assert_eq!(last_round.counts.get(lab.clone()).unwrap(), 3);
+    assert_eq!(last_round.counts.get(con).unwrap(), 2);
+    assert_eq!(last_round.eliminated.len(), 0);
+    assert_eq!(tally.outcome, RankedOutcome::Winner(lab));
+}
+
+#[test]
+fn ranked_vote_with_delegated_vote_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 4);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    let (lab, con, lib) = (symbol_short!("Laborist"), symbol_short!("Conserv"), symbol_short!("Liberal"));
+
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::RankedChoice);
+    client.delegate(&test_data.voters.get(0).unwrap(), &test_data.voters.get(1).unwrap());
+    client.vote_ranked(&test_data.voters.get(1).unwrap(), &vec![&env, lib.clone(), con.clone()]);
+    client.vote_ranked(&test_data.voters.get(2).unwrap(), &vec![&env, lab.clone()]);
+    client.vote_ranked(&test_data.voters.get(3).unwrap(), &vec![&env, con.clone()]);
+
+    env.ledger().with_mut(|l| {l.timestamp = ts_end + 1;});
+    let tally = client.tally_ranked(&test_data.admin);
+    let first_round = tally.rounds.get(0).unwrap();
+    assert_eq!(first_round.counts.get(lib.clone()).unwrap(), 2);
+    assert_eq!(first_round.eliminated, vec![&env, con.clone(), lab.clone()]);
+    assert_eq!(tally.outcome, RankedOutcome::Winner(lib));
+}
+
+#[test]
+fn ranked_vote_tie_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 2);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::RankedChoice);
+    client.vote_ranked(&test_data.voters.get(0).unwrap(), &vec![&env, symbol_short!("Laborist")]);
+    client.vote_ranked(&test_data.voters.get(1).unwrap(), &vec![&env, symbol_short!("Conserv")]);
+
+    env.ledger().with_mut(|l| {l.timestamp = ts_end + 1;});
+    let tally = client.tally_ranked(&test_data.admin);
+    assert_eq!(tally.rounds.len(), 2);
+    assert_eq!(tally.rounds.get(0).unwrap().eliminated, vec![&env, symbol_short!("Liberal")]);
+    assert_eq!(tally.outcome, RankedOutcome::Tie(vec![&env, symbol_short!("Conserv"), symbol_short!("Laborist")]));
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #8)")]
+fn vote_in_ranked_ballot_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 1);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::RankedChoice);
+    client.vote(&test_data.voters.get(0).unwrap(), &symbol_short!("Laborist"));
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #9)")]
+fn ranked_vote_duplicated_preferences_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 1);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::RankedChoice);
+    client.vote_ranked(&test_data.voters.get(0).unwrap(), &vec![&env, symbol_short!("Laborist"), symbol_short!("Laborist")]);
+}
+
+#[test]
+fn register_and_remove_candidates_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238000;});
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 0);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    client.configure(&test_data.admin, &Gating::BallotToken(test_data.token.address.clone()), &ts_start, &ts_end, &BallotMode::Plurality, &Rules::default());
+    client.register_candidates(&test_data.admin, &candidates(&env));
+    client.register_candidates(&test_data.admin, &vec![&env, symbol_short!("Laborist"), symbol_short!("Green")]);
+    assert!(client.remove_candidate(&test_data.admin, &symbol_short!("Liberal")));
+
+    let mut ids: Vec<Symbol> = Vec::new(&env);
+    for candidate in client.candidates().iter() {
+        ids.push_back(candidate.id);
+    }
+
+    assert_eq!(ids, vec![&env, symbol_short!("Laborist"), symbol_short!("Conserv"), symbol_short!("Green")]);
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #10)")]
+fn remove_unregistered_candidate_test() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 0);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::Plurality);
+    client.remove_candidate(&test_data.admin, &symbol_short!("Green"));
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #11)")]
+fn register_candidates_once_ballot_opened_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 0);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    client.configure(&test_data.admin, &Gating::BallotToken(test_data.token.address.clone()), &ts_start, &ts_end, &BallotMode::Plurality, &Rules::default());
+    client.register_candidates(&test_data.admin, &candidates(&env));
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #11)")]
+fn remove_candidate_once_ballot_opened_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238000;});
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 0);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    client.configure(&test_data.admin, &Gating::BallotToken(test_data.token.address.clone()), &ts_start, &ts_end, &BallotMode::Plurality, &Rules::default());
+    client.register_candidates(&test_data.admin, &candidates(&env));
+
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    client.remove_candidate(&test_data.admin, &symbol_short!("Liberal"));
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #10)")]
+fn vote_unregistered_candidate_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 1);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::Plurality);
+    client.vote(&test_data.voters.get(0).unwrap(), &symbol_short!("Laboristt"));
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #10)")]
+fn ranked_vote_unregistered_candidate_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 1);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::RankedChoice);
+    client.vote_ranked(&test_data.voters.get(0).unwrap(), &vec![&env, symbol_short!("Laborist"), symbol_short!("Green")]);
+}
+
+#[test]
+fn vote_cost_does_not_grow_with_turnout_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    env.budget().reset_unlimited();
+
+    // Both ballots live in the same ledger so the host storage has the same size when each vote is measured
+    let small = create_client(&env);
+    let large = create_client(&env);
+    let warmup = create_client(&env);
+    let test_data = get_test_data(&env, 1000);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    for client in [&small, &large, &warmup] {
+        open_ballot(&env, client, &test_data, ts_start, ts_end, BallotMode::Plurality);
+    }
+
+    small.vote(&test_data.voters.get(0).unwrap(), &symbol_short!("Laborist"));
+    for voter in test_data.voters.iter().take(999) {
+        large.vote(&voter, &symbol_short!("Laborist"));
+    }
+
+    // The first vote of an address pays some one-off host costs, so it is not measured
+    let last_voter = test_data.voters.get(999).unwrap();
+    warmup.vote(&last_voter, &symbol_short!("Conserv"));
+
+    env.budget().reset_default();
+    small.vote(&last_voter, &symbol_short!("Conserv"));
+    let small_cost = env.budget().cpu_instruction_cost();
+
+    env.budget().reset_default();
+    large.vote(&last_voter, &symbol_short!("Conserv"));
+    let large_cost = env.budget().cpu_instruction_cost();
+
+    env.ledger().with_mut(|l| {l.timestamp = ts_end + 1;});
+    assert_eq!(large.results().get(symbol_short!("Laborist")).unwrap(), 999);
+    assert_eq!(large.results().get(symbol_short!("Conserv")).unwrap(), 1);
+    assert!(large_cost <= small_cost + small_cost / 100);
+}
+
+#[test]
+fn reconfigure_before_start_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238000;});
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 0);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    client.configure(&test_data.admin, &Gating::BallotToken(test_data.token.address.clone()), &ts_start, &ts_end, &BallotMode::Plurality, &Rules::default());
+    assert!(client.configure(&test_data.admin, &Gating::BallotToken(test_data.token.address.clone()), &ts_start, &(ts_end + 86400), &BallotMode::RankedChoice, &Rules::default()));
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #12)")]
+fn reconfigure_from_other_address_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238000;});
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 0);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+    let intruder = Address::generate(&env);
+
+    client.configure(&test_data.admin, &Gating::BallotToken(test_data.token.address.clone()), &ts_start, &ts_end, &BallotMode::Plurality, &Rules::default());
+    client.configure(&intruder, &Gating::BallotToken(test_data.token.address.clone()), &ts_start, &ts_end, &BallotMode::Plurality, &Rules::default());
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #13)")]
+fn reconfigure_once_started_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 0);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::Plurality);
+    client.configure(&test_data.admin, &Gating::BallotToken(test_data.token.address.clone()), &ts_start, &ts_end, &BallotMode::Plurality, &Rules::default());
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #12)")]
+fn count_from_other_address_test() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 0);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::Plurality);
+    client.count(&Address::generate(&env));
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #14)")]
+fn register_candidates_before_configure_test() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 0);
+
+    client.register_candidates(&test_data.admin, &candidates(&env));
+}
+
+#[test]
+fn admin_transfer_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238000;});
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 0);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+    let new_admin = Address::generate(&env);
+
+    client.configure(&test_data.admin, &Gating::BallotToken(test_data.token.address.clone()), &ts_start, &ts_end, &BallotMode::Plurality, &Rules::default());
+    client.propose_admin(&test_data.admin, &new_admin);
+
+    // The current admin keeps the role until the transfer is accepted
+    client.register_candidates(&test_data.admin, &candidates(&env));
+    assert!(client.accept_admin(&new_admin));
+
+    client.remove_candidate(&new_admin, &symbol_short!("Liberal"));
+    assert_eq!(client.try_remove_candidate(&test_data.admin, &symbol_short!("Conserv")), Err(Ok(Error::AddressIsNotAdmin)));
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #12)")]
+fn accept_admin_from_other_address_test() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 0);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    client.configure(&test_data.admin, &Gating::BallotToken(test_data.token.address.clone()), &ts_start, &ts_end, &BallotMode::Plurality, &Rules::default());
+    client.propose_admin(&test_data.admin, &Address::generate(&env));
+    client.accept_admin(&Address::generate(&env));
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #15)")]
+fn accept_admin_without_proposal_test() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 0);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    client.configure(&test_data.admin, &Gating::BallotToken(test_data.token.address.clone()), &ts_start, &ts_end, &BallotMode::Plurality, &Rules::default());
+    client.accept_admin(&Address::generate(&env));
+}
+
+fn vote_all(env: &Env, client: &BallotClient, test_data: &TestData, votes: &[&str]) {
+    for (i, candidate) in votes.iter().enumerate() {
+        client.vote(&test_data.voters.get(i as u32).unwrap(), &Symbol::new(env, candidate));
+    }
+}
+
+#[test]
+fn result_winner_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 5);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    open_ballot_with_rules(&env, &client, &test_data, ts_start, ts_end, BallotMode::Plurality, Rules { quorum: Quorum::Percentage(6000), threshold: 5000, max_depth: 0, reveal_period: 0 });
+    vote_all(&env, &client, &test_data, &["Laborist", "Laborist", "Conserv"]);
+
+    env.ledger().with_mut(|l| {l.timestamp = ts_end + 1;});
+    assert_eq!(client.result(), BallotResult::Winner(symbol_short!("Laborist")));
+}
+
+#[test]
+fn result_no_quorum_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 5);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    open_ballot_with_rules(&env, &client, &test_data, ts_start, ts_end, BallotMode::Plurality, Rules { quorum: Quorum::Percentage(6001), threshold: 0, max_depth: 0, reveal_period: 0 });
+    vote_all(&env, &client, &test_data, &["Laborist", "Laborist", "Conserv"]);
+
+    env.ledger().with_mut(|l| {l.timestamp = ts_end + 1;});
+    assert_eq!(client.result(), BallotResult::NoQuorum);
+}
+
+#[test]
+fn result_quorum_leaves_out_expired_tokens_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 3);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    // Two memberships lapse while the ballot is open
+    env.ledger().with_mut(|l| {l.timestamp = 0;});
+    test_data.token.mint(&Address::generate(&env), &Some(ts_start + 3600));
+    test_data.token.mint(&Address::generate(&env), &Some(ts_start + 3600));
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+
+    open_ballot_with_rules(&env, &client, &test_data, ts_start, ts_end, BallotMode::Plurality, Rules { quorum: Quorum::Percentage(6000), threshold: 0, max_depth: 0, reveal_period: 0 });
+    vote_all(&env, &client, &test_data, &["Laborist", "Laborist"]);
+
+    env.ledger().with_mut(|l| {l.timestamp = ts_end + 1;});
+    assert_eq!(test_data.token.total_supply(), 5);
+    assert_eq!(client.result(), BallotResult::Winner(symbol_short!("Laborist")));
+}
+
+#[test]
+fn result_no_absolute_quorum_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 5);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    open_ballot_with_rules(&env, &client, &test_data, ts_start, ts_end, BallotMode::Plurality, Rules { quorum: Quorum::Absolute(4), threshold: 0, max_depth: 0, reveal_period: 0 });
+    vote_all(&env, &client, &test_data, &["Laborist", "Laborist", "Conserv"]);
+
+    env.ledger().with_mut(|l| {l.timestamp = ts_end + 1;});
+    assert_eq!(client.result(), BallotResult::NoQuorum);
+}
+
+#[test]
+fn result_tie_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 4);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::Plurality);
+    vote_all(&env, &client, &test_data, &["Laborist", "Conserv", "Conserv", "Laborist"]);
+
+    env.ledger().with_mut(|l| {l.timestamp = ts_end + 1;});
+    assert_eq!(client.result(), BallotResult::Tie(vec![&env, symbol_short!("Conserv"), symbol_short!("Laborist")]));
+}
+
+#[test]
+fn result_threshold_not_met_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 5);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    // Two thirds supermajority
+    open_ballot_with_rules(&env, &client, &test_data, ts_start, ts_end, BallotMode::Plurality, Rules { quorum: Quorum::Absolute(3), threshold: 6667, max_depth: 0, reveal_period: 0 });
+    vote_all(&env, &client, &test_data, &["Laborist", "Laborist", "Conserv"]);
+
+    env.ledger().with_mut(|l| {l.timestamp = ts_end + 1;});
+    assert_eq!(client.result(), BallotResult::ThresholdNotMet);
+}
+
+#[test]
+fn result_ranked_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 5);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+    let (lab, con, lib) = (symbol_short!("Laborist"), symbol_short!("Conserv"), symbol_short!("Liberal"));
+
+    // Liberal is eliminated and its ballot goes to Laborist, which ends with 60% of the votes
+    open_ballot_with_rules(&env, &client, &test_data, ts_start, ts_end, BallotMode::RankedChoice, Rules { quorum: Quorum::Absolute(5), threshold: 6000, max_depth: 0, reveal_period: 0 });
+    client.vote_ranked(&test_data.voters.get(0).unwrap(), &vec![&env, lab.clone()]);
+    client.vote_ranked(&test_data.voters.get(1).unwrap(), &vec![&env, lab.clone()]);
+    client.vote_ranked(&test_data.voters.get(2).unwrap(), &vec![&env, con.clone()]);
+    client.vote_ranked(&test_data.voters.get(3).unwrap(), &vec![&env, con.clone()]);
+    client.vote_ranked(&test_data.voters.get(4).unwrap(), &vec![&env, lib.clone(), lab.clone()]);
+
+    env.ledger().with_mut(|l| {l.timestamp = ts_end + 1;});
+    assert_eq!(client.result(), BallotResult::Winner(lab));
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #17)")]
+fn result_before_close_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 0);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::Plurality);
+    client.result();
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #16)")]
+fn configure_invalid_threshold_test() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 0);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    client.configure(&test_data.admin, &Gating::BallotToken(test_data.token.address.clone()), &ts_start, &ts_end, &BallotMode::Plurality, &Rules { quorum: Quorum::Disabled, threshold: 10001, max_depth: 0, reveal_period: 0 });
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #17)")]
+fn results_before_close_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 1);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::Plurality);
+    client.vote(&test_data.voters.get(0).unwrap(), &symbol_short!("Laborist"));
+    client.results();
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #17)")]
+fn tally_ranked_before_close_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 1);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::RankedChoice);
+    client.vote_ranked(&test_data.voters.get(0).unwrap(), &vec![&env, symbol_short!("Laborist")]);
+    client.tally_ranked(&test_data.admin);
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #10)")]
+fn register_reserved_candidate_test() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 0);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    client.configure(&test_data.admin, &Gating::BallotToken(test_data.token.address.clone()), &ts_start, &ts_end, &BallotMode::Plurality, &Rules::default());
+    client.register_candidates(&test_data.admin, &vec![&env, symbol_short!("turnout")]);
+}
+
+#[test]
+fn change_vote_with_delegated_vote_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 3);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+    let (voter1, voter2, voter3) = (test_data.voters.get(0).unwrap(), test_data.voters.get(1).unwrap(), test_data.voters.get(2).unwrap());
+
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::Plurality);
+    client.delegate(&voter1, &voter2);
+    client.vote(&voter2, &symbol_short!("Laborist"));
+    client.vote(&voter3, &symbol_short!("Conserv"));
+
+    assert!(client.change_vote(&voter2, &symbol_short!("Liberal")));
+    assert_eq!(
+        last_event(&env),
+        vec![
+            &env,
+            (
+                client.address.clone(),
+                (symbol_short!("ballot"), symbol_short!("changed"), voter2.clone()).into_val(&env),
+                (symbol_short!("Laborist"), symbol_short!("Liberal"), 2_u64).into_val(&env)
+            )
+        ]
+    );
+
+    env.ledger().with_mut(|l| {l.timestamp = ts_end + 1;});
+    let count = client.results();
+    assert_eq!(count.get(symbol_short!("Laborist")).unwrap(), 0);
+    assert_eq!(count.get(symbol_short!("Conserv")).unwrap(), 1);
+    assert_eq!(count.get(symbol_short!("Liberal")).unwrap(), 2);
+    assert_eq!(count.get(symbol_short!("turnout")).unwrap(), 2);
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #18)")]
+fn change_vote_without_voting_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 1);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::Plurality);
+    client.change_vote(&test_data.voters.get(0).unwrap(), &symbol_short!("Liberal"));
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #6)")]
+fn change_vote_once_closed_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 1);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::Plurality);
+    client.vote(&test_data.voters.get(0).unwrap(), &symbol_short!("Laborist"));
+
+    env.ledger().with_mut(|l| {l.timestamp = ts_end + 1;});
+    client.change_vote(&test_data.voters.get(0).unwrap(), &symbol_short!("Liberal"));
+}
+
+#[test]
+fn approval_vote_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 4);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+    let (lab, con, lib) = (symbol_short!("Laborist"), symbol_short!("Conserv"), symbol_short!("Liberal"));
+    let voter1 = test_data.voters.get(0).unwrap();
+
+    open_ballot_with_rules(&env, &client, &test_data, ts_start, ts_end, BallotMode::Approval(2), Rules { quorum: Quorum::Absolute(3), threshold: 6000, max_depth: 0, reveal_period: 0 });
+    client.delegate(&test_data.voters.get(3).unwrap(), &voter1);
+    client.vote_approval(&voter1, &vec![&env, lab.clone(), con.clone()]);
+    assert_eq!(
+        last_event(&env),
+        vec![
+            &env,
+            (
+                client.address.clone(),
+                (symbol_short!("ballot"), symbol_short!("approval"), voter1.clone()).into_val(&env),
+                (vec![&env, lab.clone(), con.clone()], 2_u64).into_val(&env)
+            )
+        ]
+    );
+
+    client.vote_approval(&test_data.voters.get(1).unwrap(), &vec![&env, con.clone()]);
+    client.vote_approval(&test_data.voters.get(2).unwrap(), &vec![&env, lib.clone(), lab.clone()]);
+
+    env.ledger().with_mut(|l| {l.timestamp = ts_end + 1;});
+    let count = client.results();
+    assert_eq!(count.get(lab.clone()).unwrap(), 3);
+    assert_eq!(count.get(con).unwrap(), 3);
+    assert_eq!(count.get(lib).unwrap(), 1);
+    assert_eq!(client.result(), BallotResult::Tie(vec![&env, symbol_short!("Conserv"), lab]));
+}
+
+#[test]
+fn approval_vote_threshold_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 3);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+    let (lab, con, lib) = (symbol_short!("Laborist"), symbol_short!("Conserv"), symbol_short!("Liberal"));
+
+    // Laborist is approved by two out of three voters although it only holds half of the approvals
+    open_ballot_with_rules(&env, &client, &test_data, ts_start, ts_end, BallotMode::Approval(3), Rules { quorum: Quorum::Disabled, threshold: 6666, max_depth: 0, reveal_period: 0 });
+    client.vote_approval(&test_data.voters.get(0).unwrap(), &vec![&env, lab.clone(), con]);
+    client.vote_approval(&test_data.voters.get(1).unwrap(), &vec![&env, lab.clone()]);
+    client.vote_approval(&test_data.voters.get(2).unwrap(), &vec![&env, lib]);
+
+    env.ledger().with_mut(|l| {l.timestamp = ts_end + 1;});
+    assert_eq!(client.result(), BallotResult::Winner(lab));
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #21)")]
+fn approval_vote_too_many_candidates_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 1);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::Approval(1));
+    client.vote_approval(&test_data.voters.get(0).unwrap(), &vec![&env, symbol_short!("Laborist"), symbol_short!("Conserv")]);
+}
+
+#[test]
+fn approval_vote_duplicated_candidates_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 2);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+    let (lab, con) = (symbol_short!("Laborist"), symbol_short!("Conserv"));
+
+    // A repeated candidate is approved once and only counts once against the limit
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::Approval(1));
+    let voter = test_data.voters.get(0).unwrap();
+    client.vote_approval(&voter, &vec![&env, lab.clone(), lab.clone()]);
+    assert_eq!(
+        last_event(&env),
+        vec![
+            &env,
+            (
+                client.address.clone(),
+                (symbol_short!("ballot"), symbol_short!("approval"), voter.clone()).into_val(&env),
+                (vec![&env, lab.clone()], 1_u64).into_val(&env)
+            )
+        ]
+    );
+    assert_eq!(
+        client.try_vote_approval(&test_data.voters.get(1).unwrap(), &vec![&env, lab.clone(), con.clone(), lab.clone()]),
+        Err(Ok(Error::TooManyApprovals))
+    );
+
+    env.ledger().with_mut(|l| {l.timestamp = ts_end + 1;});
+    assert_eq!(client.results().get(lab).unwrap(), 1);
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #8)")]
+fn configure_approval_without_approvals_test() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 0);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    client.configure(&test_data.admin, &Gating::BallotToken(test_data.token.address.clone()), &ts_start, &ts_end, &BallotMode::Approval(0), &Rules::default());
+}
+
+#[test]
+fn abstain_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 5);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+    let voter2 = test_data.voters.get(1).unwrap();
+
+    // Three votes out of five only reach the 60% quorum with the abstentions
+    open_ballot_with_rules(&env, &client, &test_data, ts_start, ts_end, BallotMode::Plurality, Rules { quorum: Quorum::Percentage(6000), threshold: 0, max_depth: 0, reveal_period: 0 });
+    client.vote(&test_data.voters.get(0).unwrap(), &symbol_short!("Laborist"));
+    client.delegate(&test_data.voters.get(2).unwrap(), &voter2);
+    client.abstain(&voter2);
+    assert_eq!(
+        last_event(&env),
+        vec![
+            &env,
+            (
+                client.address.clone(),
+                (symbol_short!("ballot"), symbol_short!("abstained"), voter2.clone()).into_val(&env),
+                2_u64.into_val(&env)
+            )
+        ]
+    );
+
+    env.ledger().with_mut(|l| {l.timestamp = ts_end + 1;});
+    let count = client.results();
+    assert_eq!(count.get(symbol_short!("Laborist")).unwrap(), 1);
+    assert_eq!(count.get(symbol_short!("abstain")).unwrap(), 2);
+    assert_eq!(count.get(symbol_short!("turnout")).unwrap(), 2);
+    assert_eq!(client.result(), BallotResult::Winner(symbol_short!("Laborist")));
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #2)")]
+fn vote_after_abstaining_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 1);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::Plurality);
+    client.abstain(&test_data.voters.get(0).unwrap());
+    client.vote(&test_data.voters.get(0).unwrap(), &symbol_short!("Laborist"));
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #10)")]
+fn register_abstain_candidate_test() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 0);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    client.configure(&test_data.admin, &Gating::BallotToken(test_data.token.address.clone()), &ts_start, &ts_end, &BallotMode::Plurality, &Rules::default());
+    client.register_candidates(&test_data.admin, &vec![&env, symbol_short!("abstain")]);
+}
+
+fn candidate_info(env: &Env, name: &str) -> CandidateInfo {
+    CandidateInfo {
+        name: String::from_str(env, name),
+        description: String::from_str(env, "Party manifesto summary"),
+        url: String::from_str(env, "https://example.org/manifesto"),
+        document: Document::Hash(BytesN::from_array(env, &[7; 32]))
+    }
+}
+
+#[test]
+fn candidate_details_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 1);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+    let lab = symbol_short!("Laborist");
+
+    env.ledger().with_mut(|l| {l.timestamp = ts_start - 1;});
+    client.configure(&test_data.admin, &Gating::BallotToken(test_data.token.address.clone()), &ts_start, &ts_end, &BallotMode::Plurality, &Rules::default());
+    client.register_candidates(&test_data.admin, &vec![&env, symbol_short!("Conserv")]);
+    client.set_candidate(&test_data.admin, &lab, &candidate_info(&env, "Labour Party"));
+    client.set_candidate(&test_data.admin, &lab, &candidate_info(&env, "Labour & Co-operative Party"));
+
+    env.ledger().with_mut(|l| {l.timestamp = ts_start;});
+    client.vote(&test_data.voters.get(0).unwrap(), &lab);
+
+    let expected = Candidate { id: lab.clone(), info: candidate_info(&env, "Labour & Co-operative Party"), votes: 0 };
+    assert_eq!(client.candidate(&lab), expected);
+
+    env.ledger().with_mut(|l| {l.timestamp = ts_end + 1;});
+    let candidates = client.candidates();
+    assert_eq!(candidates.len(), 2);
+    assert_eq!(candidates.get(0).unwrap().info.name, String::from_str(&env, ""));
+    assert_eq!(candidates.get(0).unwrap().info.document, Document::None);
+    assert_eq!(candidates.get(1).unwrap(), Candidate { votes: 1, ..expected });
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #11)")]
+fn set_candidate_once_ballot_opened_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 0);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::Plurality);
+    client.set_candidate(&test_data.admin, &symbol_short!("Laborist"), &candidate_info(&env, "Labour Party"));
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #10)")]
+fn unregistered_candidate_details_test() {
+    let env = Env::default();
+    let client = create_client(&env);
+
+    client.candidate(&symbol_short!("Laborist"));
+}
+
+#[test]
+fn vote_again_after_token_transfer_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 2);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+    let voter = test_data.voters.get(0).unwrap();
+    let fresh = Address::generate(&env);
+
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::Plurality);
+    client.vote(&voter, &symbol_short!("Laborist"));
+    test_data.token.transfer(&voter, &fresh, &1);
+    assert_eq!(client.try_vote(&fresh, &symbol_short!("Laborist")), Err(Ok(Error::VoterNotEligible)));
+
+    // A token transferred in the ledger the ballot opens cannot vote twice either
+    env.ledger().with_mut(|l| {l.timestamp = ts_start;});
+    let opener = test_data.voters.get(1).unwrap();
+    let other = Address::generate(&env);
+    client.vote(&opener, &symbol_short!("Laborist"));
+    test_data.token.transfer(&opener, &other, &1);
+    assert_eq!(client.try_vote(&other, &symbol_short!("Laborist")), Err(Ok(Error::VoterNotEligible)));
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #22)")]
+fn delegate_token_minted_after_start_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 1);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+    let late_holder = Address::generate(&env);
+
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::Plurality);
+    test_data.token.mint(&late_holder, &None);
+    client.delegate(&late_holder, &test_data.voters.get(0).unwrap());
+}
+
+#[test]
+fn vote_with_token_received_before_start_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 1);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+    let receiver = Address::generate(&env);
+
+    env.ledger().with_mut(|l| {l.timestamp = ts_start - 60;});
+    test_data.token.transfer(&test_data.voters.get(0).unwrap(), &receiver, &1);
+
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::Plurality);
+    env.ledger().with_mut(|l| {l.timestamp = ts_start;});
+    assert!(client.vote(&receiver, &symbol_short!("Laborist")));
+}
+
+#[test]
+fn vote_with_expired_token_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 1);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+    let member = Address::generate(&env);
+
+    env.ledger().with_mut(|l| {l.timestamp = ts_start - 60;});
+    test_data.token.mint(&member, &Some(ts_start + 3600));
+
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::Plurality);
+    env.ledger().with_mut(|l| {l.timestamp = ts_start + 3600;});
+    assert_eq!(client.try_vote(&member, &symbol_short!("Laborist")), Err(Ok(Error::VoterDoesNotHoldToken)));
+
+    test_data.token.renew(&member, &ts_end);
+    assert!(client.vote(&member, &symbol_short!("Laborist")));
+}
+
+#[test]
+fn voters_and_delegations_pages_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 4);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+    let (v1, v2, v3, v4) = (test_data.voters.get(0).unwrap(), test_data.voters.get(1).unwrap(), test_data.voters.get(2).unwrap(), test_data.voters.get(3).unwrap());
+
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::Plurality);
+    client.delegate(&v1, &v2);
+    client.vote(&v2, &symbol_short!("Laborist"));
+    client.abstain(&v3);
+    client.vote(&v4, &symbol_short!("Conserv"));
+
+    env.ledger().with_mut(|l| {l.timestamp = ts_end + 1;});
+    assert_eq!(
+        client.voters(&0, &2),
+        vec![
+            &env,
+            VoterEntry { voter: v2.clone(), choice: Choice::Single(symbol_short!("Laborist")), weight: 2 },
+            VoterEntry { voter: v3, choice: Choice::Abstain, weight: 1 }
+        ]
+    );
+    assert_eq!(client.voters(&2, &2), vec![&env, VoterEntry { voter: v4, choice: Choice::Single(symbol_short!("Conserv")), weight: 1 }]);
+    assert_eq!(client.voters(&5, &2).len(), 0);
+    assert_eq!(client.delegations(&0, &10), vec![&env, DelegationEntry { delegator: v1, delegate: v2 }]);
+}
+
+#[test]
+fn voters_page_size_is_capped_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 52);
+    env.budget().reset_unlimited();
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::Plurality);
+    for voter in test_data.voters.iter() {
+        client.vote(&voter, &symbol_short!("Laborist"));
+    }
+
+    env.ledger().with_mut(|l| {l.timestamp = ts_end + 1;});
+    assert_eq!(client.voters(&0, &u32::MAX).len(), 50);
+    assert_eq!(client.voters(&50, &u32::MAX).len(), 2);
+}
+
+#[test]
+fn tally_at_scale_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    env.budget().reset_unlimited();
+    let mut clients = Vec::new(&env);
+    for _ in 0..4 {
+        clients.push_back(create_client(&env).address);
+    }
+
+    let test_data = get_test_data(&env, 150);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+    let (lab, con, lib) = (symbol_short!("Laborist"), symbol_short!("Conserv"), symbol_short!("Liberal"));
+
+    // The small ballots take the votes of the first three voters only, the large ones of every voter
+    for (c, address) in clients.iter().enumerate() {
+        let mode = if c < 2 { BallotMode::RankedChoice } else { BallotMode::Approval(2) };
+        open_ballot(&env, &BallotClient::new(&env, &address), &test_data, ts_start, ts_end, mode);
+    }
+
+    for (i, voter) in test_data.voters.iter().enumerate() {
+        let preferences = match i % 3 {
+            0 => vec![&env, lab.clone(), con.clone()],
+            1 => vec![&env, con.clone(), lab.clone()],
+            _ => vec![&env, lib.clone(), lab.clone()]
+        };
+
+        for (c, address) in clients.iter().enumerate() {
+            if c % 2 == 0 && i >= 3 {
+                continue;
+            }
+
+            let client = BallotClient::new(&env, &address);
+            if c < 2 {
+                client.vote_ranked(&voter, &preferences);
+            } else {
+                client.vote_approval(&voter, &vec![&env, lab.clone(), con.clone()]);
+            }
+        }
+    }
+
+    // Tallies read the aggregates rather than every voter record, so their cost does not grow with the turnout
+    env.ledger().with_mut(|l| {l.timestamp = ts_end + 1;});
+    let mut costs = [0u64; 4];
+    for (c, address) in clients.iter().enumerate() {
+        let client = BallotClient::new(&env, &address);
+        env.budget().reset_default();
+        let result = client.finalize(&test_data.admin, &TokenRelease::Keep).result;
+        costs[c] = env.budget().cpu_instruction_cost();
+        if c < 2 {
+            assert_eq!(result, BallotResult::Tie(vec![&env, con.clone(), lab.clone(), lib.clone()]));
+        } else {
+            assert_eq!(result, BallotResult::Tie(vec![&env, con.clone(), lab.clone()]));
+        }
+    }
+
+    assert!(costs[1] < costs[0] * 11 / 10);
+    assert!(costs[3] < costs[2] * 11 / 10);
+}
+
+#[test]
+fn ranked_lists_at_scale_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    env.budget().reset_unlimited();
+    let small = create_client(&env);
+    let large = create_client(&env);
+    let test_data = get_test_data(&env, 302);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+    let mut names = candidates(&env);
+    names.push_back(symbol_short!("Green"));
+    names.push_back(symbol_short!("Pirate"));
+    names.push_back(symbol_short!("Local"));
+
+    env.ledger().with_mut(|l| {l.timestamp = ts_start - 1;});
+    for client in [&small, &large] {
+        client.configure(&test_data.admin, &Gating::BallotToken(test_data.token.address.clone()), &ts_start, &ts_end, &BallotMode::RankedChoice, &Rules::default());
+        client.register_candidates(&test_data.admin, &names);
+    }
+
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+
+    // Every voter of the large ballot ranks the six candidates in a different order
+    let ordering = |i: u32| {
+        let mut pool = names.clone();
+        let mut preferences = Vec::new(&env);
+        let mut rest = i;
+        while !pool.is_empty() {
+            let pick = rest % pool.len();
+            rest /= pool.len();
+            preferences.push_back(pool.get(pick).unwrap());
+            pool.remove(pick);
+        }
+
+        preferences
+    };
+
+    for i in 0..300 {
+        large.vote_ranked(&test_data.voters.get(i).unwrap(), &ordering(i));
+    }
+
+    small.vote_ranked(&test_data.voters.get(0).unwrap(), &ordering(0));
+
+    // Each preference list has its own entry, so a vote costs the same however many lists were cast before
+    let voter = test_data.voters.get(300).unwrap();
+    env.budget().reset_default();
+    small.vote_ranked(&voter, &ordering(300));
+    let small_cost = env.budget().cpu_instruction_cost();
+
+    env.budget().reset_default();
+    large.vote_ranked(&voter, &ordering(300));
+    assert!(env.budget().cpu_instruction_cost() < small_cost * 11 / 10);
+
+    let voter = test_data.voters.get(301).unwrap();
+    env.budget().reset_unlimited();
+    large.vote_ranked(&voter, &ordering(0));
+    env.ledger().with_mut(|l| {l.timestamp = ts_end + 1;});
+    let result = large.finalize(&test_data.admin, &TokenRelease::Keep).result;
+    assert_eq!(result, BallotResult::Winner(names.get(0).unwrap()));
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #17)")]
+fn voters_before_close_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 1);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::Plurality);
+    client.vote(&test_data.voters.get(0).unwrap(), &symbol_short!("Laborist"));
+    client.voters(&0, &10);
+}
+
+#[test]
+fn configure_event_test() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 0);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    client.configure(&test_data.admin, &Gating::BallotToken(test_data.token.address.clone()), &ts_start, &ts_end, &BallotMode::Plurality, &Rules::default());
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            (
+                client.address.clone(),
+                (symbol_short!("ballot"), symbol_short!("configure"), test_data.admin.clone()).into_val(&env),
+                (Gating::BallotToken(test_data.token.address.clone()), ts_start, ts_end, BallotMode::Plurality, Rules::default()).into_val(&env)
+            )
+        ]
+    );
+}
+
+#[test]
+fn vote_and_delegate_events_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 2);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+    let voter1 = test_data.voters.get(0).unwrap();
+    let voter2 = test_data.voters.get(1).unwrap();
+
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::Plurality);
+
+    client.delegate(&voter1, &voter2);
+    assert_eq!(
+        last_event(&env),
+        vec![
+            &env,
+            (
+                client.address.clone(),
+                (symbol_short!("ballot"), symbol_short!("delegated"), voter1.clone()).into_val(&env),
+                voter2.into_val(&env)
+            )
+        ]
+    );
+
+    client.vote(&voter2, &symbol_short!("Laborist"));
+    assert_eq!(
+        last_event(&env),
+        vec![
+            &env,
+            (
+                client.address.clone(),
+                (symbol_short!("ballot"), symbol_short!("voted"), voter2.clone()).into_val(&env),
+                (symbol_short!("Laborist"), 2_u64).into_val(&env)
+            )
+        ]
+    );
+
+    env.ledger().with_mut(|l| {l.timestamp = ts_end + 1;});
+    let count = client.count(&test_data.admin);
+    let mut expected: Map<Symbol, u64> = Map::new(&env);
+    expected.set(symbol_short!("Laborist"), 2);
+    expected.set(symbol_short!("Conserv"), 0);
+    expected.set(symbol_short!("Liberal"), 0);
+    expected.set(symbol_short!("abstain"), 0);
+    expected.set(symbol_short!("turnout"), 1);
+
+    assert_eq!(count, expected);
+    assert_eq!(
+        last_event(&env),
+        vec![
+            &env,
+            (
+                client.address.clone(),
+                (symbol_short!("ballot"), symbol_short!("counted")).into_val(&env),
+                expected.into_val(&env)
+            )
+        ]
+    );
+}
+
+#[test]
+fn ranked_vote_and_tally_events_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 1);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+    let voter = test_data.voters.get(0).unwrap();
+    let preferences = vec![&env, symbol_short!("Liberal"), symbol_short!("Laborist")];
+
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::RankedChoice);
+
+    client.vote_ranked(&voter, &preferences);
+    assert_eq!(
+        last_event(&env),
+        vec![
+            &env,
+            (
+                client.address.clone(),
+                (symbol_short!("ballot"), symbol_short!("ranked"), voter.clone()).into_val(&env),
+                (preferences, 1_u64).into_val(&env)
+            )
+        ]
+    );
+
+    env.ledger().with_mut(|l| {l.timestamp = ts_end + 1;});
+    client.tally_ranked(&test_data.admin);
+    assert_eq!(
+        last_event(&env),
+        vec![
+            &env,
+            (
+                client.address.clone(),
+                (symbol_short!("ballot"), symbol_short!("tallied")).into_val(&env),
+                RankedOutcome::Winner(symbol_short!("Liberal")).into_val(&env)
+            )
+        ]
+    );
+}
+
+/// Registers a Stellar asset, funds each voter with the given balance and opens a ballot gated on it
+fn open_sep41_ballot(env: &Env, client: &BallotClient, admin: &Address, balances: &[i128], weighting: Weighting) -> Vec<Address> {
+    let asset = env.register_stellar_asset_contract(admin.clone());
+    let sac = StellarAssetClient::new(env, &asset);
+    let mut voters = Vec::new(env);
+    for balance in balances {
+        let addr = Address::generate(env);
+        sac.mint(&addr, balance);
+        voters.push_back(addr);
+    }
+
+    let gating = Gating::Sep41(TokenGate { token: asset, min_balance: 100, weighting });
+    let now = env.ledger().timestamp();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238799;});
+    client.configure(admin, &gating, &1689238800, &1689551999, &BallotMode::Plurality, &Rules::default());
+    client.register_candidates(admin, &candidates(env));
+    env.ledger().with_mut(|l| {l.timestamp = now;});
+    voters
+}
+
+#[test]
+fn sep41_min_balance_test() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let admin = Address::generate(&env);
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+
+    let voters = open_sep41_ballot(&env, &client, &admin, &[100, 99], Weighting::Flat);
+    client.vote(&voters.get(0).unwrap(), &symbol_short!("Laborist"));
+    assert_eq!(client.try_vote(&voters.get(1).unwrap(), &symbol_short!("Laborist")), Err(Ok(Error::VoterDoesNotHoldToken)));
+}
+
+#[test]
+fn sep41_weighting_test() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let admin = Address::generate(&env);
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+
+    let voters = open_sep41_ballot(&env, &client, &admin, &[100, 450, 999], Weighting::Linear);
+    client.vote(&voters.get(0).unwrap(), &symbol_short!("Laborist"));
+    client.vote(&voters.get(1).unwrap(), &symbol_short!("Conserv"));
+    client.vote(&voters.get(2).unwrap(), &symbol_short!("Liberal"));
+
+    let sqrt_client = create_client(&env);
+    let sqrt_voters = open_sep41_ballot(&env, &sqrt_client, &admin, &[100, 450, 999], Weighting::Sqrt);
+    sqrt_client.vote(&sqrt_voters.get(0).unwrap(), &symbol_short!("Laborist"));
+    sqrt_client.vote(&sqrt_voters.get(1).unwrap(), &symbol_short!("Conserv"));
+    sqrt_client.vote(&sqrt_voters.get(2).unwrap(), &symbol_short!("Liberal"));
+
+    env.ledger().with_mut(|l| {l.timestamp = 1689552000;});
+    let linear = client.results();
+    assert_eq!(linear.get(symbol_short!("Laborist")).unwrap(), 1);
+    assert_eq!(linear.get(symbol_short!("Conserv")).unwrap(), 4);
+    assert_eq!(linear.get(symbol_short!("Liberal")).unwrap(), 9);
+
+    let sqrt = sqrt_client.results();
+    assert_eq!(sqrt.get(symbol_short!("Laborist")).unwrap(), 1);
+    assert_eq!(sqrt.get(symbol_short!("Conserv")).unwrap(), 2);
+    assert_eq!(sqrt.get(symbol_short!("Liberal")).unwrap(), 3);
+    assert_eq!(sqrt_client.result(), BallotResult::Winner(symbol_short!("Liberal")));
+}
+
+#[test]
+fn sep41_large_weights_test() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let admin = Address::generate(&env);
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+
+    let whale = 100 * u32::MAX as i128;
+    let voters = open_sep41_ballot(&env, &client, &admin, &[whale, 2 * whale, 100, 100], Weighting::Linear);
+    client.vote(&voters.get(0).unwrap(), &symbol_short!("Laborist"));
+    client.vote(&voters.get(1).unwrap(), &symbol_short!("Conserv"));
+    client.vote(&voters.get(2).unwrap(), &symbol_short!("Liberal"));
+    client.abstain(&voters.get(3).unwrap());
+
+    // Balances above u32::MAX units count as u32::MAX units and later ballots still fit in the counters
+    env.ledger().with_mut(|l| {l.timestamp = 1689552000;});
+    let results = client.results();
+    assert_eq!(results.get(symbol_short!("Laborist")).unwrap(), u32::MAX as u64);
+    assert_eq!(results.get(symbol_short!("Conserv")).unwrap(), u32::MAX as u64);
+    assert_eq!(results.get(symbol_short!("Liberal")).unwrap(), 1);
+    assert_eq!(results.get(symbol_short!("abstain")).unwrap(), 1);
+    assert_eq!(client.result(), BallotResult::Tie(vec![&env, symbol_short!("Conserv"), symbol_short!("Laborist")]));
+}
+
+#[test]
+fn sep41_vote_again_after_token_transfer_test() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let admin = Address::generate(&env);
+    let asset = env.register_stellar_asset_contract(admin.clone());
+    let tk = TokenClient::new(&env, &asset);
+    let voter = Address::generate(&env);
+    let accomplice = Address::generate(&env);
+    StellarAssetClient::new(&env, &asset).mint(&voter, &250);
+
+    env.ledger().with_mut(|l| {l.timestamp = 1689238799;});
+    let gating = Gating::Sep41(TokenGate { token: asset, min_balance: 100, weighting: Weighting::Linear });
+    client.configure(&admin, &gating, &1689238800, &1689551999, &BallotMode::Plurality, &Rules::default());
+    client.register_candidates(&admin, &candidates(&env));
+
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    client.vote(&voter, &symbol_short!("Laborist"));
+    assert_eq!(tk.balance(&voter), 0);
+    assert_eq!(tk.balance(&client.address), 250);
+    assert!(tk.try_transfer(&voter, &accomplice, &250).is_err());
+    assert_eq!(client.try_vote(&accomplice, &symbol_short!("Laborist")), Err(Ok(Error::VoterDoesNotHoldToken)));
+    assert_eq!(client.try_refund(&voter), Err(Ok(Error::BallotNotClosed)));
+
+    env.ledger().with_mut(|l| {l.timestamp = 1689552000;});
+    assert_eq!(client.refund(&voter), 250);
+    assert_eq!(tk.balance(&voter), 250);
+    assert_eq!(client.try_refund(&voter), Err(Ok(Error::NothingToRefund)));
+    assert_eq!(client.results().get(symbol_short!("Laborist")).unwrap(), 2);
+
+    // Without an end date the ballot never closes, so the tokens cannot be taken back to vote again
+    let open_client = create_client(&env);
+    open_client.configure(&admin, &gating, &0, &0, &BallotMode::Plurality, &Rules::default());
+    open_client.register_candidates(&admin, &candidates(&env));
+    open_client.vote(&voter, &symbol_short!("Laborist"));
+    assert_eq!(open_client.try_refund(&voter), Err(Ok(Error::BallotNotClosed)));
+    assert_eq!(tk.balance(&voter), 0);
+}
+
+#[test]
+fn sep41_delegation_not_available_test() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let admin = Address::generate(&env);
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+
+    let voters = open_sep41_ballot(&env, &client, &admin, &[100, 100], Weighting::Flat);
+    assert_eq!(client.try_delegate(&voters.get(0).unwrap(), &voters.get(1).unwrap()), Err(Ok(Error::DelegationNotAvailable)));
+}
+
+#[test]
+fn sep41_invalid_gating_test() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let admin = Address::generate(&env);
+    let asset = env.register_stellar_asset_contract(admin.clone());
+
+    let gating = Gating::Sep41(TokenGate { token: asset.clone(), min_balance: 0, weighting: Weighting::Flat });
+    assert_eq!(client.try_configure(&admin, &gating, &1689238800, &1689551999, &BallotMode::Plurality, &Rules::default()), Err(Ok(Error::InvalidGating)));
+
+    let gating = Gating::Sep41(TokenGate { token: asset, min_balance: 100, weighting: Weighting::Flat });
+    let rules = Rules { quorum: Quorum::Percentage(5000), threshold: 0, max_depth: 0, reveal_period: 0 };
+    assert_eq!(client.try_configure(&admin, &gating, &1689238800, &1689551999, &BallotMode::Plurality, &rules), Err(Ok(Error::InvalidRules)));
+}
+
+fn hash_pair(env: &Env, a: &BytesN<32>, b: &BytesN<32>) -> BytesN<32> {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+    let mut data = Bytes::from_array(env, &left.to_array());
+    data.append(&Bytes::from_array(env, &right.to_array()));
+    env.crypto().sha256(&data)
+}
+
+/// Member of an anonymous ballot allowlist, holding its ed25519 key, nullifier and Merkle proof
+struct Member {
+    key: SigningKey,
+    nullifier: BytesN<32>,
+    proof: Vec<BytesN<32>>
+}
+
+impl Member {
+    fn voter_key(&self, env: &Env) -> BytesN<32> {
+        BytesN::from_array(env, &self.key.verifying_key().to_bytes())
+    }
+
+    /// Signs `xdr(ballot) || xdr(candidate) || nullifier`
+    fn sign(&self, env: &Env, ballot: &Address, candidate: &Symbol) -> BytesN<64> {
+        let mut message = ballot.clone().to_xdr(env);
+        message.append(&candidate.clone().to_xdr(env));
+        message.append(&Bytes::from_array(env, &self.nullifier.to_array()));
+
+        let mut buf = [0u8; 128];
+        let len = message.len() as usize;
+        message.copy_into_slice(&mut buf[..len]);
+        BytesN::from_array(env, &self.key.sign(&buf[..len]).to_bytes())
+    }
+
+    fn vote(&self, env: &Env, client: &BallotClient, relayer: &Address, candidate: &str) -> bool {
+        let candidate = Symbol::new(env, candidate);
+        let signature = self.sign(env, &client.address, &candidate);
+        client.vote_anonymous(relayer, &candidate, &self.nullifier, &self.voter_key(env), &self.proof, &signature)
+    }
+}
+
+/// Builds a four member allowlist, each leaf being `sha256(voter_key || nullifier)`, and returns its root
+fn merkle_allowlist(env: &Env) -> (BytesN<32>, [Member; 4]) {
+    let keys = [1u8, 2, 3, 4].map(|i| SigningKey::from_bytes(&[i; 32]));
+    let nullifiers = [11u8, 12, 13, 14].map(|i| BytesN::from_array(env, &[i; 32]));
+    let leaves = [0, 1, 2, 3].map(|i| {
+        let mut data = Bytes::from_array(env, &keys[i].verifying_key().to_bytes());
+        data.append(&Bytes::from_array(env, &nullifiers[i].to_array()));
+        env.crypto().sha256(&data)
+    });
+
+    let left = hash_pair(env, &leaves[0], &leaves[1]);
+    let right = hash_pair(env, &leaves[2], &leaves[3]);
+    let root = hash_pair(env, &left, &right);
+    let proofs = [
+        vec![env, leaves[1].clone(), right.clone()],
+        vec![env, leaves[0].clone(), right],
+        vec![env, leaves[3].clone(), left.clone()],
+        vec![env, leaves[2].clone(), left]
+    ];
+
+    let [k0, k1, k2, k3] = keys;
+    let [n0, n1, n2, n3] = nullifiers;
+    let [p0, p1, p2, p3] = proofs;
+    (root, [
+        Member { key: k0, nullifier: n0, proof: p0 },
+        Member { key: k1, nullifier: n1, proof: p1 },
+        Member { key: k2, nullifier: n2, proof: p2 },
+        Member { key: k3, nullifier: n3, proof: p3 }
+    ])
+}
+
+fn open_anonymous_ballot(env: &Env, client: &BallotClient, admin: &Address, root: BytesN<32>) {
+    env.ledger().with_mut(|l| {l.timestamp = 1689238799;});
+    client.configure(admin, &Gating::Merkle(root), &1689238800, &1689551999, &BallotMode::Plurality, &Rules::default());
+    client.register_candidates(admin, &candidates(env));
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+}
+
+#[test]
+fn vote_anonymous_test() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let admin = Address::generate(&env);
+    let relayer = Address::generate(&env);
+    let (root, members) = merkle_allowlist(&env);
+
+    open_anonymous_ballot(&env, &client, &admin, root);
+    members[0].vote(&env, &client, &relayer, "Laborist");
+    members[2].vote(&env, &client, &relayer, "Laborist");
+    members[3].vote(&env, &client, &relayer, "Conserv");
+    assert_eq!(
+        last_event(&env),
+        vec![
+            &env,
+            (
+                client.address.clone(),
+                (symbol_short!("ballot"), symbol_short!("anon"), members[3].nullifier.clone()).into_val(&env),
+                symbol_short!("Conserv").into_val(&env)
+            )
+        ]
+    );
+
+    env.ledger().with_mut(|l| {l.timestamp = 1689552000;});
+    let count = client.results();
+    assert_eq!(count.get(symbol_short!("Laborist")).unwrap(), 2);
+    assert_eq!(count.get(symbol_short!("Conserv")).unwrap(), 1);
+    assert_eq!(count.get(symbol_short!("turnout")).unwrap(), 3);
+    assert_eq!(client.try_voters(&0, &10), Err(Ok(Error::AnonymousBallot)));
+}
+
+#[test]
+fn vote_anonymous_rejected_test() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let admin = Address::generate(&env);
+    let relayer = Address::generate(&env);
+    let (root, members) = merkle_allowlist(&env);
+    let conserv = symbol_short!("Conserv");
+
+    open_anonymous_ballot(&env, &client, &admin, root);
+    members[1].vote(&env, &client, &relayer, "Laborist");
+
+    let other = Address::generate(&env);
+    let (m1, m2) = (&members[1], &members[2]);
+    let signature = m1.sign(&env, &client.address, &conserv);
+    assert_eq!(client.try_vote_anonymous(&other, &conserv, &m1.nullifier, &m1.voter_key(&env), &m1.proof, &signature), Err(Ok(Error::NullifierAlreadySpent)));
+
+    // Proofs only hold for the key and nullifier committed in the leaf
+    let signature = m2.sign(&env, &client.address, &conserv);
+    assert_eq!(client.try_vote_anonymous(&relayer, &conserv, &m2.nullifier, &m2.voter_key(&env), &m1.proof, &signature), Err(Ok(Error::InvalidMerkleProof)));
+    assert_eq!(client.try_vote_anonymous(&relayer, &conserv, &m2.nullifier, &m1.voter_key(&env), &m2.proof, &signature), Err(Ok(Error::InvalidMerkleProof)));
+    assert_eq!(client.try_vote_anonymous(&relayer, &conserv, &BytesN::from_array(&env, &[9; 32]), &m2.voter_key(&env), &m2.proof, &signature), Err(Ok(Error::InvalidMerkleProof)));
+    assert_eq!(client.try_vote(&relayer, &conserv), Err(Ok(Error::AnonymousBallot)));
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Crypto, InvalidInput)")]
+fn vote_anonymous_relayer_cannot_change_candidate_test() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let admin = Address::generate(&env);
+    let relayer = Address::generate(&env);
+    let (root, members) = merkle_allowlist(&env);
+
+    open_anonymous_ballot(&env, &client, &admin, root);
+    let m0 = &members[0];
+    let signature = m0.sign(&env, &client.address, &symbol_short!("Laborist"));
+    // The signature check traps, so the vote is cast within the contract frame instead of through the client
+    let _ = env.as_contract(&client.address, || {
+        Ballot::vote_anonymous(env.clone(), relayer, symbol_short!("Conserv"), m0.nullifier.clone(), m0.voter_key(&env), m0.proof.clone(), signature)
+    });
+}
+
+#[test]
+fn vote_anonymous_in_token_ballot_test() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 1);
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    let (_, members) = merkle_allowlist(&env);
+
+    open_ballot(&env, &client, &test_data, 1689238800, 1689551999, BallotMode::Plurality);
+    let m0 = &members[0];
+    let signature = m0.sign(&env, &client.address, &symbol_short!("Laborist"));
+    assert_eq!(
+        client.try_vote_anonymous(&test_data.admin, &symbol_short!("Laborist"), &m0.nullifier, &m0.voter_key(&env), &m0.proof, &signature),
+        Err(Ok(Error::InvalidGating))
+    );
+}
+
+#[test]
+fn anonymous_ballot_needs_plurality_test() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let admin = Address::generate(&env);
+    let (root, _) = merkle_allowlist(&env);
+
+    for mode in [BallotMode::RankedChoice, BallotMode::Approval(2), BallotMode::Stv(2)] {
+        assert_eq!(client.try_configure(&admin, &Gating::Merkle(root.clone()), &1689238800, &1689551999, &mode, &Rules::default()), Err(Ok(Error::InvalidBallotMode)));
+    }
+}
+
+fn seal(env: &Env, candidate: Symbol, salt: &BytesN<32>) -> BytesN<32> {
+    let mut data = candidate.to_xdr(env);
+    data.append(&Bytes::from_array(env, &salt.to_array()));
+    env.crypto().sha256(&data)
+}
+
+fn commit_reveal_rules() -> Rules {
+    Rules { reveal_period: 86400, ..Rules::default() }
+}
+
+#[test]
+fn commit_reveal_test() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 4);
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+    let (a, b, c, d) = (test_data.voters.get(0).unwrap(), test_data.voters.get(1).unwrap(), test_data.voters.get(2).unwrap(), test_data.voters.get(3).unwrap());
+    let salt = BytesN::from_array(&env, &[7; 32]);
+
+    open_ballot_with_rules(&env, &client, &test_data, ts_start, ts_end, BallotMode::Plurality, commit_reveal_rules());
+    client.delegate(&a, &b);
+    client.commit_vote(&b, &seal(&env, symbol_short!("Laborist"), &salt));
+    client.commit_vote(&c, &seal(&env, symbol_short!("Conserv"), &salt));
+    client.commit_vote(&d, &seal(&env, symbol_short!("Liberal"), &salt));
+    assert_eq!(client.try_reveal_vote(&b, &symbol_short!("Laborist"), &salt), Err(Ok(Error::BallotOutOfDate)));
+
+    env.ledger().with_mut(|l| {l.timestamp = ts_end + 1;});
+    assert_eq!(client.try_results(), Err(Ok(Error::BallotNotClosed)));
+    assert_eq!(client.try_reveal_vote(&b, &symbol_short!("Conserv"), &salt), Err(Ok(Error::InvalidReveal)));
+    client.reveal_vote(&b, &symbol_short!("Laborist"), &salt);
+    client.reveal_vote(&c, &symbol_short!("Conserv"), &salt);
+    assert_eq!(client.try_reveal_vote(&c, &symbol_short!("Conserv"), &salt), Err(Ok(Error::VoteNotCommitted)));
+    assert_eq!(
+        last_event(&env),
+        vec![
+            &env,
+            (
+                client.address.clone(),
+                (symbol_short!("ballot"), symbol_short!("revealed"), c.clone()).into_val(&env),
+                (symbol_short!("Conserv"), 1_u64).into_val(&env)
+            )
+        ]
+    );
+
+    env.ledger().with_mut(|l| {l.timestamp = ts_end + 86401;});
+    assert_eq!(client.try_reveal_vote(&d, &symbol_short!("Liberal"), &salt), Err(Ok(Error::BallotOutOfDate)));
+    let count = client.results();
+    assert_eq!(count.get(symbol_short!("Laborist")).unwrap(), 2);
+    assert_eq!(count.get(symbol_short!("Conserv")).unwrap(), 1);
+    assert_eq!(count.get(symbol_short!("Liberal")).unwrap(), 0);
+    assert_eq!(count.get(symbol_short!("sealed")).unwrap(), 1);
+    assert_eq!(count.get(symbol_short!("turnout")).unwrap(), 3);
+    assert_eq!(client.result(), BallotResult::Winner(symbol_short!("Laborist")));
+}
+
+#[test]
+fn commit_reveal_rejects_open_votes_test() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 1);
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+    let voter = test_data.voters.get(0).unwrap();
+    let gating = Gating::BallotToken(test_data.token.address.clone());
+
+    assert_eq!(client.try_configure(&test_data.admin, &gating, &ts_start, &ts_end, &BallotMode::RankedChoice, &commit_reveal_rules()), Err(Ok(Error::InvalidRules)));
+    open_ballot_with_rules(&env, &client, &test_data, ts_start, ts_end, BallotMode::Plurality, commit_reveal_rules());
+    assert_eq!(client.try_vote(&voter, &symbol_short!("Laborist")), Err(Ok(Error::CommitRevealBallot)));
+    assert_eq!(client.try_abstain(&voter), Err(Ok(Error::CommitRevealBallot)));
+}
+
+#[test]
+fn finalize_test() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 3);
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+    let rules = Rules { quorum: Quorum::Percentage(5000), ..Rules::default() };
+
+    open_ballot_with_rules(&env, &client, &test_data, ts_start, ts_end, BallotMode::Plurality, rules.clone());
+    vote_all(&env, &client, &test_data, &["Laborist", "Laborist", "Conserv"]);
+    assert_eq!(client.try_finalize(&test_data.admin, &TokenRelease::Burn), Err(Ok(Error::BallotNotClosed)));
+
+    env.ledger().with_mut(|l| {l.timestamp = ts_end + 1;});
+    let certificate = client.finalize(&test_data.admin, &TokenRelease::Burn);
+    assert_eq!(certificate, Certificate { results: client.results(), result: BallotResult::Winner(symbol_short!("Laborist")), finalized_at: ts_end + 1 });
+    assert_eq!(
+        last_event(&env),
+        vec![
+            &env,
+            (
+                client.address.clone(),
+                (symbol_short!("ballot"), symbol_short!("certified"), test_data.admin.clone()).into_val(&env),
+                certificate.clone().into_val(&env)
+            )
+        ]
+    );
+
+    // The tokens are gone but the certified outcome does not change
+    assert_eq!(test_data.token.balance(&test_data.voters.get(0).unwrap()), 0);
+    assert_eq!(test_data.token.total_supply(), 0);
+    assert_eq!(client.result(), BallotResult::Winner(symbol_short!("Laborist")));
+    assert_eq!(client.certificate(), certificate);
+
+    let gating = Gating::BallotToken(test_data.token.address.clone());
+    assert_eq!(client.try_finalize(&test_data.admin, &TokenRelease::Keep), Err(Ok(Error::BallotFinalized)));
+    assert_eq!(client.try_configure(&test_data.admin, &gating, &ts_start, &(ts_end + 86400), &BallotMode::Plurality, &rules), Err(Ok(Error::BallotFinalized)));
+}
+
+#[test]
+fn finalize_close_minting_test() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 2);
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::Plurality);
+    assert_eq!(client.try_certificate(), Err(Ok(Error::BallotNotFinalized)));
+
+    env.ledger().with_mut(|l| {l.timestamp = ts_end + 1;});
+    assert_eq!(client.finalize(&test_data.admin, &TokenRelease::CloseMinting).result, BallotResult::NoQuorum);
+    assert_eq!(test_data.token.balance(&test_data.voters.get(0).unwrap()), 1);
+    assert!(test_data.token.try_mint(&Address::generate(&env), &None).is_err());
+}
+
+#[test]
+fn finalize_unlock_test() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let admin = Address::generate(&env);
+    let voter = Address::generate(&env);
+    let token = token::Client::new(&env, &env.register_contract_wasm(None, token::WASM));
+    token.initialize(&admin, &token::TransferPolicy::Soulbound);
+    token.mint(&voter, &None);
+    let test_data = TestData { admin, voters: vec![&env, voter.clone()], token };
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    open_ballot(&env, &client, &test_data, ts_start, ts_end, BallotMode::Plurality);
+    client.vote(&voter, &symbol_short!("Laborist"));
+
+    env.ledger().with_mut(|l| {l.timestamp = ts_end + 1;});
+    client.finalize(&test_data.admin, &TokenRelease::Unlock);
+    assert_eq!(test_data.token.transfer_policy(), token::TransferPolicy::Transferable);
+    assert!(test_data.token.try_mint(&Address::generate(&env), &None).is_err());
+
+    let to = Address::generate(&env);
+    test_data.token.transfer(&voter, &to, &1);
+    assert_eq!(test_data.token.balance(&to), 1);
+}
+
+#[test]
+fn finalize_sep41_release_test() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let admin = Address::generate(&env);
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+
+    open_sep41_ballot(&env, &client, &admin, &[100], Weighting::Flat);
+    env.ledger().with_mut(|l| {l.timestamp = 1689552000;});
+    assert_eq!(client.try_finalize(&admin, &TokenRelease::Burn), Err(Ok(Error::InvalidGating)));
+    client.finalize(&admin, &TokenRelease::Keep);
+}
+
+/// Opens an STV ballot for the given candidates and casts the ranked ballots, each group being a number of
+/// voters sharing the same preferences
+fn stv_election<'a>(env: &'a Env, client: &BallotClient, seats: u32, names: &[&str], groups: &[(u32, &[&str])]) -> TestData<'a> {
+    let voters: u32 = groups.iter().map(|(n, _)| n).sum();
+    let test_data = get_test_data(env, voters);
+    let candidates = symbols(env, names);
+    let gating = Gating::BallotToken(test_data.token.address.clone());
+
+    env.ledger().with_mut(|l| {l.timestamp = 1689238799;});
+    client.configure(&test_data.admin, &gating, &1689238800, &1689551999, &BallotMode::Stv(seats), &Rules::default());
+    client.register_candidates(&test_data.admin, &candidates);
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+
+    let mut i = 0;
+    for (n, preferences) in groups {
+        let preferences = symbols(env, preferences);
+        for _ in 0..*n {
+            client.vote_ranked(&test_data.voters.get(i).unwrap(), &preferences);
+            i += 1;
+        }
+    }
+
+    env.ledger().with_mut(|l| {l.timestamp = 1689552000;});
+    test_data
+}
+
+fn stv_counts(env: &Env, counts: &[(&str, u128)]) -> Map<Symbol, u128> {
+    let mut map: Map<Symbol, u128> = Map::new(env);
+    for (c, n) in counts {
+        map.set(Symbol::new(env, c), *n);
+    }
+
+    map
+}
+
+fn symbols(env: &Env, names: &[&str]) -> Vec<Symbol> {
+    let mut list: Vec<Symbol> = Vec::new(env);
+    for n in names {
+        list.push_back(Symbol::new(env, n));
+    }
+
+    list
+}
+
+/// Reference election from the Wikipedia single transferable vote article: 20 voters, 3 seats, quota 6
+#[test]
+fn stv_reference_election_test() {
+    let env = Env::default();
+    env.budget().reset_unlimited();
+    let client = create_client(&env);
+    let test_data = stv_election(&env, &client, 3, &["Orange", "Pear", "Choc", "Strawb", "Bonbon"], &[
+        (4, &["Orange"]),
+        (2, &["Pear", "Orange"]),
+        (8, &["Choc", "Strawb"]),
+        (4, &["Choc", "Bonbon"]),
+        (1, &["Strawb"]),
+        (1, &["Bonbon"])
+    ]);
+
+    let tally = client.tally_stv(&test_data.admin);
+    assert_eq!(tally.quota, 600_000);
+    assert_eq!(tally.elected, symbols(&env, &["Choc", "Orange", "Strawb"]));
+    assert_eq!(tally.rounds.len(), 5);
+    assert_eq!(tally.rounds.get(0).unwrap(), StvRound {
+        counts: stv_counts(&env, &[("Orange", 400_000), ("Pear", 200_000), ("Choc", 1_200_000), ("Strawb", 100_000), ("Bonbon", 100_000)]),
+        elected: symbols(&env, &["Choc"]),
+        excluded: Vec::new(&env),
+        exhausted: 0
+    });
+
+    // Chocolate's surplus of 6 moves on at half the value of its 12 ballots
+    assert_eq!(tally.rounds.get(1).unwrap(), StvRound {
+        counts: stv_counts(&env, &[("Orange", 400_000), ("Pear", 200_000), ("Strawb", 500_000), ("Bonbon", 300_000)]),
+        elected: Vec::new(&env),
+        excluded: symbols(&env, &["Pear"]),
+        exhausted: 0
+    });
+    assert_eq!(tally.rounds.get(2).unwrap().elected, symbols(&env, &["Orange"]));
+    assert_eq!(tally.rounds.get(3).unwrap().excluded, symbols(&env, &["Bonbon"]));
+    assert_eq!(tally.rounds.get(4).unwrap().counts, stv_counts(&env, &[("Strawb", 500_000)]));
+    assert_eq!(tally.rounds.get(4).unwrap().exhausted, 300_000);
+    assert_eq!(client.result(), BallotResult::Elected(symbols(&env, &["Choc", "Orange", "Strawb"])));
+}
+
+/// Two candidates reach the quota in the first round and both surpluses are transferred
+#[test]
+fn stv_simultaneous_surplus_test() {
+    let env = Env::default();
+    env.budget().reset_unlimited();
+    let client = create_client(&env);
+    let test_data = stv_election(&env, &client, 3, &["Ann", "Bob", "Cid", "Dan"], &[
+        (5, &["Ann", "Cid"]),
+        (5, &["Bob", "Cid"]),
+        (1, &["Cid"]),
+        (2, &["Dan"])
+    ]);
+
+    let tally = client.tally_stv(&test_data.admin);
+    assert_eq!(tally.quota, 400_000);
+    assert_eq!(tally.rounds.get(0).unwrap().elected, symbols(&env, &["Ann", "Bob"]));
+    assert_eq!(tally.rounds.get(1).unwrap().counts, stv_counts(&env, &[("Cid", 300_000), ("Dan", 200_000)]));
+    assert_eq!(tally.rounds.get(1).unwrap().excluded, symbols(&env, &["Dan"]));
+    assert_eq!(tally.rounds.get(2).unwrap().exhausted, 200_000);
+    assert_eq!(tally.elected, symbols(&env, &["Ann", "Bob", "Cid"]));
+}
+
+/// Surplus values are truncated to 1 / 100000 of a vote: 7 ballots carrying a surplus of 2 move on
+/// at 0.28571 each instead of 2 / 7
+#[test]
+fn stv_fractional_surplus_test() {
+    let env = Env::default();
+    env.budget().reset_unlimited();
+    let client = create_client(&env);
+    let test_data = stv_election(&env, &client, 2, &["Ann", "Bob", "Cid"], &[
+        (4, &["Ann", "Bob"]),
+        (3, &["Ann", "Cid"]),
+        (2, &["Bob"]),
+        (4, &["Cid"])
+    ]);
+
+    let tally = client.tally_stv(&test_data.admin);
+    assert_eq!(tally.quota, 500_000);
+    assert_eq!(tally.rounds.get(1).unwrap(), StvRound {
+        counts: stv_counts(&env, &[("Bob", 314_285), ("Cid", 485_714)]),
+        elected: Vec::new(&env),
+        excluded: symbols(&env, &["Bob"]),
+        exhausted: 0
+    });
+    assert_eq!(tally.elected, symbols(&env, &["Ann", "Cid"]));
+}
+
+#[test]
+fn stv_large_weights_test() {
+    let env = Env::default();
+    let (ann, bob, cid) = (Symbol::new(&env, "Ann"), Symbol::new(&env, "Bob"), Symbol::new(&env, "Cid"));
+    let k: u64 = 1 << 50;
+    let ballots = vec![
+        &env,
+        RankedBallot { preferences: vec![&env, ann.clone(), bob.clone()], weight: 7 * k },
+        RankedBallot { preferences: vec![&env, bob.clone()], weight: 2 * k },
+        RankedBallot { preferences: vec![&env, cid.clone()], weight: 4 * k }
+    ];
+
+    // Ann's surplus transfer multiplies two values beyond 2^64, past what fits in 128 bits
+    let tally = single_transferable_vote(&env, vec![&env, ann.clone(), bob.clone(), cid.clone()], 2, &ballots);
+    let quota = (13 * k as u128 / 3 + 1) * STV_SCALE;
+    let surplus = 7 * k as u128 * STV_SCALE - quota;
+    assert_eq!(tally.quota, quota);
+    assert_eq!(tally.rounds.get(1).unwrap().counts.get(bob.clone()).unwrap(), 2 * k as u128 * STV_SCALE + surplus);
+    assert_eq!(tally.elected, vec![&env, ann, bob]);
+}
+
+#[test]
+fn stv_configuration_test() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let test_data = get_test_data(&env, 1);
+    let gating = Gating::BallotToken(test_data.token.address.clone());
+    let rules = Rules { threshold: 5000, ..Rules::default() };
+
+    assert_eq!(client.try_configure(&test_data.admin, &gating, &1689238800, &1689551999, &BallotMode::Stv(0), &Rules::default()), Err(Ok(Error::InvalidBallotMode)));
+    assert_eq!(client.try_configure(&test_data.admin, &gating, &1689238800, &1689551999, &BallotMode::Stv(2), &rules), Err(Ok(Error::InvalidRules)));
+
+    open_ballot(&env, &client, &test_data, 1689238800, 1689551999, BallotMode::Stv(2));
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    assert_eq!(client.try_vote(&test_data.voters.get(0).unwrap(), &symbol_short!("Laborist")), Err(Ok(Error::InvalidBallotMode)));
+    assert_eq!(client.try_tally_stv(&test_data.admin), Err(Ok(Error::BallotNotClosed)));
 }
 
-fn create_client(env: &Env) -> BallotClient{
+fn create_client(env: &Env) -> BallotClient<'_>{
     env.mock_all_auths();
 
     let contract_id = env.register_contract(None, Ballot);
-    let client = BallotClient::new(&env, &contract_id);
+    let client = BallotClient::new(env, &contract_id);
     client
 }
\ No newline at end of file
@@ -0,0 +1,11 @@
+use soroban_sdk::{symbol_short, Address, BytesN, Env, Symbol};
+
+pub const FACTORY: Symbol = symbol_short!("factory");
+
+pub fn created(env: &Env, admin: Address, id: u32, ballot: Address, token: Address) {
+    env.events().publish((FACTORY, symbol_short!("created"), admin), (id, ballot, token));
+}
+
+pub fn wasm_updated(env: &Env, admin: Address, ballot_wasm_hash: BytesN<32>, token_wasm_hash: BytesN<32>) {
+    env.events().publish((FACTORY, symbol_short!("wasm"), admin), (ballot_wasm_hash, token_wasm_hash));
+}
@@ -0,0 +1,129 @@
+#![no_std]
+
+use soroban_sdk::{contract, contractimpl, contracterror, Address, BytesN, Env, Vec};
+
+mod events;
+mod storage;
+
+mod ballot {
+    soroban_sdk::contractimport!(
+        file = "../Ballot/target/wasm32-unknown-unknown/release/ballot_v3.wasm"
+    );
+}
+
+mod token {
+    soroban_sdk::contractimport!(
+        file = "../BallotToken/target/wasm32-unknown-unknown/release/ballot_token.wasm"
+    );
+}
+
+use ballot::{BallotMode, Rules};
+use token::TransferPolicy;
+use storage::{BallotEntry, WasmHashes};
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Error {
+    FactoryAlreadyInitialized = 1,
+    FactoryNotInitialized = 2,
+    BallotNotFound = 3
+}
+
+#[contract]
+pub struct BallotFactory;
+
+#[contractimpl]
+impl BallotFactory {
+
+    /// Stores the hashes of the uploaded Ballot and BallotToken wasm used to deploy every new ballot
+    pub fn initialize(env: Env, admin: Address, ballot_wasm_hash: BytesN<32>, token_wasm_hash: BytesN<32>) -> Result<bool, Error> {
+        if storage::has_admin(&env) {
+            return Err(Error::FactoryAlreadyInitialized);
+        }
+
+        admin.require_auth();
+        storage::store_admin(&env, &admin);
+        storage::store_wasm_hashes(&env, &WasmHashes { ballot: ballot_wasm_hash, token: token_wasm_hash });
+        Ok(true)
+    }
+
+    /// Replaces the Ballot and BallotToken wasm deployed from now on, ballots already created are not affected
+    pub fn update_wasm_hashes(env: Env, ballot_wasm_hash: BytesN<32>, token_wasm_hash: BytesN<32>) -> Result<bool, Error> {
+        let admin = match storage::get_admin(&env) {
+            Some(a) => a,
+            None => return Err(Error::FactoryNotInitialized)
+        };
+
+        admin.require_auth();
+        storage::store_wasm_hashes(&env, &WasmHashes { ballot: ballot_wasm_hash.clone(), token: token_wasm_hash.clone() });
+        events::wasm_updated(&env, admin, ballot_wasm_hash, token_wasm_hash);
+        Ok(true)
+    }
+
+    /// Deploys a BallotToken and a Ballot linked to it, both administered by `admin`, and configures the
+    /// ballot. Addresses are derived from the ballot id so they can be known before the ballot is created.
+    /// Ballots are created on behalf of the factory admin, who has to authorize the call as well.
+    pub fn create_ballot(env: Env, admin: Address, ts_start: u64, ts_end: u64, mode: BallotMode, rules: Rules, policy: TransferPolicy) -> Result<BallotEntry, Error> {
+        let (factory_admin, hashes) = match (storage::get_admin(&env), storage::get_wasm_hashes(&env)) {
+            (Some(a), Some(h)) => (a, h),
+            _ => return Err(Error::FactoryNotInitialized)
+        };
+
+        factory_admin.require_auth();
+        admin.require_auth();
+
+        let id = storage::get_ballots_count(&env);
+        let token_address = env
+            .deployer()
+            .with_current_contract(storage::salt(&env, storage::TOKEN_SALT, id))
+            .deploy(hashes.token);
+
+        let ballot_address = env
+            .deployer()
+            .with_current_contract(storage::salt(&env, storage::BALLOT_SALT, id))
+            .deploy(hashes.ballot);
+
+        token::Client::new(&env, &token_address).initialize(&admin, &policy);
+        ballot::Client::new(&env, &ballot_address).configure(&admin, &ballot::Gating::BallotToken(token_address.clone()), &ts_start, &ts_end, &mode, &rules);
+
+        let entry = BallotEntry {
+            id,
+            ballot: ballot_address.clone(),
+            token: token_address.clone(),
+            admin: admin.clone(),
+            from: ts_start,
+            to: ts_end,
+            mode,
+            rules,
+            policy
+        };
+
+        storage::store_ballot(&env, &entry);
+        events::created(&env, admin, id, ballot_address, token_address);
+        Ok(entry)
+    }
+
+    pub fn ballot(env: Env, id: u32) -> Result<BallotEntry, Error> {
+        match storage::get_ballot(&env, id) {
+            Some(entry) => Ok(entry),
+            None => Err(Error::BallotNotFound)
+        }
+    }
+
+    /// Ballots in the order they were created, pages are capped to `MAX_PAGE_SIZE` entries
+    pub fn ballots(env: Env, offset: u32, limit: u32) -> Vec<BallotEntry> {
+        let total = storage::get_ballots_count(&env);
+        let start = offset.min(total);
+        let end = start.saturating_add(limit.min(storage::MAX_PAGE_SIZE)).min(total);
+
+        let mut entries: Vec<BallotEntry> = Vec::new(&env);
+        for id in start..end {
+            entries.push_back(storage::get_ballot(&env, id).unwrap());
+        }
+
+        entries
+    }
+}
+
+mod test;
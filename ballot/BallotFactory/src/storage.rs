@@ -0,0 +1,98 @@
+use soroban_sdk::{contracttype, symbol_short, Address, BytesN, Env, Symbol};
+
+use crate::ballot::{BallotMode, Rules};
+use crate::token::TransferPolicy;
+
+pub const ADMIN: Symbol = symbol_short!("admin");
+pub const WASM: Symbol = symbol_short!("wasm");
+pub const COUNT: Symbol = symbol_short!("count");
+
+pub const DAY_IN_LEDGERS: u32 = 17280;
+pub const ENTRY_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
+pub const ENTRY_LIFETIME_THRESHOLD: u32 = ENTRY_BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+pub const MAX_PAGE_SIZE: u32 = 50;
+
+/// Salt prefixes so that the Ballot and BallotToken deployed for the same id get different addresses
+pub const BALLOT_SALT: u8 = 1;
+pub const TOKEN_SALT: u8 = 2;
+
+/// Hashes of the Ballot and BallotToken wasm already uploaded to the network
+#[derive(Clone)]
+#[contracttype]
+pub struct WasmHashes {
+    pub ballot: BytesN<32>,
+    pub token: BytesN<32>
+}
+
+/// Registry entry of a deployed ballot, with the configuration it was created with
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct BallotEntry {
+    pub id: u32,
+    pub ballot: Address,
+    pub token: Address,
+    pub admin: Address,
+    pub from: u64,
+    pub to: u64,
+    pub mode: BallotMode,
+    pub rules: Rules,
+    pub policy: TransferPolicy
+}
+
+#[contracttype]
+pub enum FactoryKey {
+    Ballot(u32)
+}
+
+pub fn has_admin(env: &Env) -> bool {
+    env.storage().instance().has(&ADMIN)
+}
+
+pub fn get_admin(env: &Env) -> Option<Address> {
+    env.storage().instance().get(&ADMIN)
+}
+
+pub fn store_admin(env: &Env, admin: &Address) {
+    env.storage().instance().set(&ADMIN, admin);
+}
+
+pub fn store_wasm_hashes(env: &Env, hashes: &WasmHashes) {
+    env.storage().instance().set(&WASM, hashes);
+}
+
+pub fn get_wasm_hashes(env: &Env) -> Option<WasmHashes> {
+    env.storage().instance().get(&WASM)
+}
+
+pub fn get_ballots_count(env: &Env) -> u32 {
+    env.storage().instance().get(&COUNT).unwrap_or(0)
+}
+
+/// Deterministic salt built from the kind of contract and the ballot id
+pub fn salt(env: &Env, kind: u8, id: u32) -> BytesN<32> {
+    let mut bytes = [0u8; 32];
+    bytes[0] = kind;
+    bytes[28..].copy_from_slice(&id.to_be_bytes());
+    BytesN::from_array(env, &bytes)
+}
+
+pub fn store_ballot(env: &Env, entry: &BallotEntry) {
+    let key = FactoryKey::Ballot(entry.id);
+    env.storage().persistent().set(&key, entry);
+    env.storage().persistent().extend_ttl(&key, ENTRY_LIFETIME_THRESHOLD, ENTRY_BUMP_AMOUNT);
+    env.storage().instance().set(&COUNT, &(entry.id + 1));
+}
+
+pub fn get_ballot(env: &Env, id: u32) -> Option<BallotEntry> {
+    let key = FactoryKey::Ballot(id);
+    if let Some(entry) = env.storage().persistent().get::<FactoryKey, BallotEntry>(&key) {
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, ENTRY_LIFETIME_THRESHOLD, ENTRY_BUMP_AMOUNT);
+
+        return Some(entry);
+    }
+
+    None
+}
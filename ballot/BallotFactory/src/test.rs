@@ -0,0 +1,123 @@
+#![cfg(test)]
+
+use super::{ballot, token, BallotFactory, BallotFactoryClient};
+use soroban_sdk::{symbol_short, testutils::{Address as _, Ledger}, vec, Address, Env};
+
+fn rules() -> ballot::Rules {
+    ballot::Rules { quorum: ballot::Quorum::Disabled, threshold: 0, max_depth: 0, reveal_period: 0 }
+}
+
+#[test]
+fn create_ballot_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238000;});
+    let client = create_client(&env);
+    let admin = Address::generate(&env);
+    let voter = Address::generate(&env);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    let entry = client.create_ballot(&admin, &ts_start, &ts_end, &ballot::BallotMode::Plurality, &rules(), &token::TransferPolicy::Transferable);
+    assert_eq!(entry.id, 0);
+    assert_eq!(entry.admin, admin);
+    assert_eq!(entry.from, ts_start);
+    assert_eq!(entry.to, ts_end);
+    assert_eq!(client.ballot(&0), entry);
+    assert_eq!(client.ballots(&0, &10), vec![&env, entry.clone()]);
+
+    // The election admin manages both the token and the ballot
+    let tk = token::Client::new(&env, &entry.token);
+    let blt = ballot::Client::new(&env, &entry.ballot);
+    tk.mint(&voter, &None);
+    blt.register_candidates(&admin, &vec![&env, symbol_short!("Laborist"), symbol_short!("Conserv")]);
+
+    env.ledger().with_mut(|l| {l.timestamp = ts_start;});
+    blt.vote(&voter, &symbol_short!("Laborist"));
+
+    env.ledger().with_mut(|l| {l.timestamp = ts_end + 1;});
+    assert_eq!(blt.results().get(symbol_short!("Laborist")).unwrap(), 1);
+}
+
+#[test]
+fn create_several_ballots_test() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let admin1 = Address::generate(&env);
+    let admin2 = Address::generate(&env);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    let entry1 = client.create_ballot(&admin1, &ts_start, &ts_end, &ballot::BallotMode::Plurality, &rules(), &token::TransferPolicy::Transferable);
+    let entry2 = client.create_ballot(&admin2, &ts_start, &ts_end, &ballot::BallotMode::RankedChoice, &rules(), &token::TransferPolicy::Soulbound);
+
+    assert_eq!(entry2.id, 1);
+    assert_ne!(entry1.ballot, entry2.ballot);
+    assert_ne!(entry1.token, entry2.token);
+    assert_eq!(token::Client::new(&env, &entry2.token).transfer_policy(), token::TransferPolicy::Soulbound);
+    assert_eq!(client.ballots(&1, &10), vec![&env, entry2]);
+    assert_eq!(client.ballots(&0, &u32::MAX).len(), 2);
+}
+
+#[test]
+fn create_ballot_needs_factory_admin_test() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register_contract(None, BallotFactory);
+    let client = BallotFactoryClient::new(&env, &contract_id);
+    let factory_admin = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let ballot_hash = env.deployer().upload_contract_wasm(ballot::WASM);
+    let token_hash = env.deployer().upload_contract_wasm(token::WASM);
+    client.initialize(&factory_admin, &ballot_hash, &token_hash);
+
+    client.create_ballot(&admin, &1689238800, &1689551999, &ballot::BallotMode::Plurality, &rules(), &token::TransferPolicy::Transferable);
+    let auths = env.auths();
+    assert!(auths.iter().any(|(addr, _)| *addr == factory_admin));
+    assert!(auths.iter().any(|(addr, _)| *addr == admin));
+
+    assert!(client.update_wasm_hashes(&ballot_hash, &token_hash));
+    assert!(env.auths().iter().any(|(addr, _)| *addr == factory_admin));
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #1)")]
+fn initialize_twice_test() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let hash = env.deployer().upload_contract_wasm(token::WASM);
+
+    client.initialize(&Address::generate(&env), &hash, &hash);
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #2)")]
+fn create_ballot_before_initialize_test() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register_contract(None, BallotFactory);
+    let client = BallotFactoryClient::new(&env, &contract_id);
+
+    client.create_ballot(&Address::generate(&env), &1689238800, &1689551999, &ballot::BallotMode::Plurality, &rules(), &token::TransferPolicy::Transferable);
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #3)")]
+fn ballot_not_found_test() {
+    let env = Env::default();
+    let client = create_client(&env);
+
+    client.ballot(&0);
+}
+
+fn create_client(env: &Env) -> BallotFactoryClient<'_> {
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, BallotFactory);
+    let client = BallotFactoryClient::new(env, &contract_id);
+    let ballot_hash = env.deployer().upload_contract_wasm(ballot::WASM);
+    let token_hash = env.deployer().upload_contract_wasm(token::WASM);
+    client.initialize(&Address::generate(env), &ballot_hash, &token_hash);
+    client
+}
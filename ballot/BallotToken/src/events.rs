@@ -0,0 +1,25 @@
+use soroban_sdk::{symbol_short, Address, Env};
+
+pub fn mint(e: &Env, admin: Address, to: Address, amount: i128) {
+    e.events().publish((symbol_short!("mint"), admin, to), amount);
+}
+
+pub fn clawback(e: &Env, admin: Address, from: Address, amount: i128) {
+    e.events().publish((symbol_short!("clawback"), admin, from), amount);
+}
+
+pub fn renew(e: &Env, admin: Address, addr: Address, expires_at: u64) {
+    e.events().publish((symbol_short!("renew"), admin, addr), expires_at);
+}
+
+pub fn approve(e: &Env, from: Address, spender: Address, amount: i128, expiration_ledger: u32) {
+    e.events().publish((symbol_short!("approve"), from, spender), (amount, expiration_ledger));
+}
+
+pub fn transfer(e: &Env, from: Address, to: Address, amount: i128) {
+    e.events().publish((symbol_short!("transfer"), from, to), amount);
+}
+
+pub fn burn(e: &Env, from: Address, amount: i128) {
+    e.events().publish((symbol_short!("burn"), from), amount);
+}
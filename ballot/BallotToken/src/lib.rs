@@ -1,7 +1,17 @@
 #![no_std]
 
-use soroban_sdk::{contract, contracttype, contractimpl, contracterror, symbol_short, Address, Env, Symbol};
+use soroban_sdk::{contract, contracttype, contractimpl, contracterror, symbol_short, Address, Env, Map, String, Symbol, Vec};
+
+mod events;
+
 pub const TOKEN_ADMIN: Symbol = symbol_short!("t_admin");
+pub const SUPPLY: Symbol = symbol_short!("supply");
+pub const RETIRED: Symbol = symbol_short!("retired");
+pub const POLICY: Symbol = symbol_short!("policy");
+
+pub const DECIMALS: u32 = 0;
+pub const NAME: &str = "Ballot Token";
+pub const SYMBOL: &str = "BALLOT";
 
 pub const DAY_IN_LEDGERS: u32 = 17280;
 pub const INSTANCE_BUMP_AMOUNT: u32 = 7 * DAY_IN_LEDGERS;
@@ -10,6 +20,16 @@ pub const INSTANCE_LIFETIME_THRESHOLD: u32 = INSTANCE_BUMP_AMOUNT - DAY_IN_LEDGE
 pub const BALANCE_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
 pub const BALANCE_LIFETIME_THRESHOLD: u32 = BALANCE_BUMP_AMOUNT - DAY_IN_LEDGERS;
 
+pub const MAX_PAGE_SIZE: u32 = 50;
+
+pub const DAY_IN_SECONDS: u64 = 86400;
+/// Expiries are counted in a tree of persistent nodes, each one splitting its range of days in
+/// 2^EXPIRY_NODE_BITS parts, so counting the expired tokens reads EXPIRY_LEVELS nodes whatever their number
+const EXPIRY_NODE_BITS: u32 = 4;
+const EXPIRY_LEVELS: u32 = 5;
+/// Tokens cannot expire after this day, counted from the Unix epoch
+pub const LAST_EXPIRY_DAY: u64 = (1 << (EXPIRY_NODE_BITS * EXPIRY_LEVELS)) - 1;
+
 #[contracterror]
 #[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
 #[repr(u32)]
@@ -17,54 +37,414 @@ pub enum Error {
     TokenAlreadyInitialized = 1,
     AddressAlreadyHoldsToken = 2,
     AddressDoesNotHoldToken = 3,
-    AddressAlreadyHasAllowance = 4,
-    ExpirationLedgerLessThanCurrentLedger = 5
+    ExpirationLedgerLessThanCurrentLedger = 5,
+    TokenRetired = 6,
+    /// Every address holds at most one token, so amounts can only be 1 (or 0 to clear an allowance)
+    InvalidAmount = 7,
+    InsufficientAllowance = 8,
+    TransferNotAllowed = 9,
+    InvalidExpiration = 10
+}
+
+/// Whether holders can move their token. `AdminApproved` transfers also need the admin signature and
+/// `Soulbound` tokens can only be minted, burned or clawed back.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum TransferPolicy {
+    Transferable,
+    AdminApproved,
+    Soulbound
+}
+
+/// Outcome of a batch operation for each address, in the order the addresses were given
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum BatchOutcome {
+    Minted,
+    Burned,
+    AlreadyHoldsToken,
+    DoesNotHoldToken
+}
+
+/// Token held by an address. `expires_at` is the timestamp from which the token no longer counts, 0 when
+/// it never expires.
+#[derive(Clone)]
+#[contracttype]
+pub struct Holding {
+    pub amount: u32,
+    pub expires_at: u64
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct AllowanceKey {
+    pub from: Address,
+    pub spender: Address
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct AllowanceValue {
+    pub amount: i128,
+    pub expiration_ledger: u32
 }
 
 #[derive(Clone)]
 #[contracttype]
 pub enum DataKey {
     Balance(Address),
-    Allowance(Address),
-    Blocking(Address)
+    Allowance(AllowanceKey),
+    HolderSince(Address),
+    Holder(u32),
+    HolderIndex(Address),
+    /// Tokens expiring in each part of a node of the expiry tree, by level and node
+    ExpiryNode(u32, u64),
+    /// Tokens expiring at each timestamp of a day
+    ExpiryDay(u64)
 }
 
+#[allow(clippy::let_and_return)]
 fn has_admin(e: &Env) -> bool {
     let has_admin = e.storage().instance().has(&TOKEN_ADMIN);
     has_admin
 }
 
-fn get_balance(e: &Env, addr: Address)-> u32 {
+/// None while the token is in use, otherwise whether its tokens were burned when retired
+fn get_retired(e: &Env) -> Option<bool> {
+    e.storage().instance().get(&RETIRED)
+}
+
+fn is_burned(e: &Env) -> bool {
+    get_retired(e).unwrap_or(false)
+}
+
+/// Token stored for the address, expired or not
+fn get_holding(e: &Env, addr: Address) -> Option<Holding> {
+    if is_burned(e) {
+        return None;
+    }
+
+    e.storage().persistent().get(&DataKey::Balance(addr))
+}
+
+fn store_holding(e: &Env, addr: Address, holding: &Holding) {
     let key = DataKey::Balance(addr);
-    if let Some(b) = e.storage().persistent().get::<DataKey, u32>(&key) {
+    e.storage().persistent().set(&key, holding);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, BALANCE_LIFETIME_THRESHOLD, BALANCE_BUMP_AMOUNT);
+}
+
+fn is_expired(e: &Env, holding: &Holding) -> bool {
+    holding.expires_at > 0 && e.ledger().timestamp() >= holding.expires_at
+}
+
+/// Expired tokens count as a zero balance and are no longer kept alive by reads
+fn get_balance(e: &Env, addr: Address)-> u32 {
+    match get_holding(e, addr.clone()) {
+        Some(holding) if !is_expired(e, &holding) => {
+            e.storage()
+                .persistent()
+                .extend_ttl(&DataKey::Balance(addr), BALANCE_LIFETIME_THRESHOLD, BALANCE_BUMP_AMOUNT);
+
+            holding.amount
+        },
+        _ => 0
+    }
+}
+
+/// Turns an optional expiration into the stored one, which must be in the future
+fn check_expiration(e: &Env, expires_at: Option<u64>) -> Result<u64, Error> {
+    match expires_at {
+        None => Ok(0),
+        Some(ts) if ts > e.ledger().timestamp() && ts / DAY_IN_SECONDS <= LAST_EXPIRY_DAY => Ok(ts),
+        Some(_) => Err(Error::InvalidExpiration)
+    }
+}
+
+/// Ledger timestamp at which the address got its token, ballots use it to freeze eligibility at their start
+fn get_holder_since(e: &Env, addr: Address) -> u64 {
+    if is_burned(e) {
+        return 0;
+    }
+
+    let key = DataKey::HolderSince(addr);
+    if let Some(ts) = e.storage().persistent().get::<DataKey, u64>(&key) {
         e.storage()
             .persistent()
             .extend_ttl(&key, BALANCE_LIFETIME_THRESHOLD, BALANCE_BUMP_AMOUNT);
 
-        return b;
+        return ts;
     }
 
     0
 }
 
-fn get_allowance(e: &Env, addr: Address) -> bool {
-    let allowance_key = DataKey::Allowance(addr);
-    if let Some(_a) = e.storage().temporary().get::<_, Address>(&allowance_key) {
-        return true;
+fn store_holder_since(e: &Env, addr: Address) {
+    let key = DataKey::HolderSince(addr);
+    e.storage().persistent().set(&key, &e.ledger().timestamp());
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, BALANCE_LIFETIME_THRESHOLD, BALANCE_BUMP_AMOUNT);
+}
+
+fn get_policy(e: &Env) -> TransferPolicy {
+    e.storage().instance().get(&POLICY).unwrap_or(TransferPolicy::Transferable)
+}
+
+/// Checks the transfer policy before a token moves between holders
+fn check_transfer(e: &Env) -> Result<(), Error> {
+    match get_policy(e) {
+        TransferPolicy::Transferable => Ok(()),
+        TransferPolicy::AdminApproved => {
+            let admin: Address = e.storage().instance().get(&TOKEN_ADMIN).unwrap();
+            admin.require_auth();
+            Ok(())
+        },
+        TransferPolicy::Soulbound => Err(Error::TransferNotAllowed)
     }
+}
 
-    false
+fn get_supply(e: &Env) -> u32 {
+    e.storage().instance().get(&SUPPLY).unwrap_or(0)
 }
 
-fn get_blocking(e: &Env, addr: Address) -> bool {
-    let blocking_key = DataKey::Blocking(addr);
-    if let Some(_b) = e.storage().temporary().get::<_, Address>(&blocking_key) {
-        return true;
+/// Stores the holder at the given position of the holders index. Holders occupy positions 0 to supply - 1
+fn store_holder(e: &Env, index: u32, addr: Address) {
+    let holder_key = DataKey::Holder(index);
+    let index_key = DataKey::HolderIndex(addr.clone());
+    e.storage().persistent().set(&holder_key, &addr);
+    e.storage().persistent().set(&index_key, &index);
+    e.storage()
+        .persistent()
+        .extend_ttl(&holder_key, BALANCE_LIFETIME_THRESHOLD, BALANCE_BUMP_AMOUNT);
+    e.storage()
+        .persistent()
+        .extend_ttl(&index_key, BALANCE_LIFETIME_THRESHOLD, BALANCE_BUMP_AMOUNT);
+}
+
+fn get_holder(e: &Env, index: u32) -> Address {
+    let key = DataKey::Holder(index);
+    let addr: Address = e.storage().persistent().get(&key).unwrap();
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, BALANCE_LIFETIME_THRESHOLD, BALANCE_BUMP_AMOUNT);
+
+    addr
+}
+
+fn get_holder_index(e: &Env, addr: Address) -> u32 {
+    let key = DataKey::HolderIndex(addr);
+    let index: u32 = e.storage().persistent().get(&key).unwrap();
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, BALANCE_LIFETIME_THRESHOLD, BALANCE_BUMP_AMOUNT);
+
+    index
+}
+
+/// Removes a holder from the index by moving the last holder into its position
+fn remove_holder(e: &Env, addr: Address) {
+    let index = get_holder_index(e, addr.clone());
+    let last = get_supply(e) - 1;
+    if index != last {
+        store_holder(e, index, get_holder(e, last));
     }
 
-    false
+    e.storage().persistent().remove(&DataKey::Holder(last));
+    e.storage().persistent().remove(&DataKey::HolderIndex(addr));
 }
 
+/// Node of the expiry tree holding `day` at `level`, with the part of the node the day falls in
+fn expiry_slot(day: u64, level: u32) -> (u64, u32) {
+    let shift = EXPIRY_NODE_BITS * level;
+    (day >> (shift + EXPIRY_NODE_BITS), ((day >> shift) & ((1 << EXPIRY_NODE_BITS) - 1)) as u32)
+}
+
+fn get_expiry_node(e: &Env, level: u32, node: u64) -> Vec<u32> {
+    let key = DataKey::ExpiryNode(level, node);
+    if let Some(counts) = e.storage().persistent().get::<DataKey, Vec<u32>>(&key) {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, BALANCE_LIFETIME_THRESHOLD, BALANCE_BUMP_AMOUNT);
+
+        return counts;
+    }
+
+    let mut counts: Vec<u32> = Vec::new(e);
+    for _ in 0..(1 << EXPIRY_NODE_BITS) {
+        counts.push_back(0);
+    }
+
+    counts
+}
+
+/// Number of tokens expiring at each timestamp of the day
+fn get_expiry_day(e: &Env, day: u64) -> Map<u64, u32> {
+    let key = DataKey::ExpiryDay(day);
+    if let Some(expiries) = e.storage().persistent().get::<DataKey, Map<u64, u32>>(&key) {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, BALANCE_LIFETIME_THRESHOLD, BALANCE_BUMP_AMOUNT);
+
+        return expiries;
+    }
+
+    Map::new(e)
+}
+
+fn update_expiries(e: &Env, expires_at: u64, added: bool) {
+    if expires_at == 0 {
+        return;
+    }
+
+    let day = expires_at / DAY_IN_SECONDS;
+    for level in 0..EXPIRY_LEVELS {
+        let (node, slot) = expiry_slot(day, level);
+        let key = DataKey::ExpiryNode(level, node);
+        let mut counts = get_expiry_node(e, level, node);
+        let count = counts.get(slot).unwrap();
+        counts.set(slot, if added { count + 1 } else { count - 1 });
+        e.storage().persistent().set(&key, &counts);
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, BALANCE_LIFETIME_THRESHOLD, BALANCE_BUMP_AMOUNT);
+    }
+
+    let key = DataKey::ExpiryDay(day);
+    let mut expiries = get_expiry_day(e, day);
+    let count = expiries.get(expires_at).unwrap_or(0);
+    if added {
+        expiries.set(expires_at, count + 1);
+    } else if count > 1 {
+        expiries.set(expires_at, count - 1);
+    } else {
+        expiries.remove(expires_at);
+    }
+
+    if expiries.is_empty() {
+        e.storage().persistent().remove(&key);
+        return;
+    }
+
+    e.storage().persistent().set(&key, &expiries);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, BALANCE_LIFETIME_THRESHOLD, BALANCE_BUMP_AMOUNT);
+}
+
+/// Supply left once the tokens expired at `at` are taken out. The tree gives the tokens expiring before the
+/// day of `at`, the entry of that day the ones expiring earlier in the day.
+fn get_live_supply(e: &Env, at: u64) -> u32 {
+    let day = at / DAY_IN_SECONDS;
+    let mut expired: u32 = 0;
+    if day > LAST_EXPIRY_DAY {
+        for count in get_expiry_node(e, EXPIRY_LEVELS - 1, 0).iter() {
+            expired += count;
+        }
+
+        return get_supply(e) - expired;
+    }
+
+    for level in 0..EXPIRY_LEVELS {
+        let (node, slot) = expiry_slot(day, level);
+        let counts = get_expiry_node(e, level, node);
+        for i in 0..slot {
+            expired += counts.get(i).unwrap();
+        }
+    }
+
+    for (expires_at, count) in get_expiry_day(e, day).iter() {
+        if expires_at > at {
+            break;
+        }
+
+        expired += count;
+    }
+
+    get_supply(e) - expired
+}
+
+fn mint_token(e: &Env, addr: Address, expires_at: u64) -> u32 {
+    let amount: u32 = 1;
+    let supply = get_supply(e);
+    store_holding(e, addr.clone(), &Holding { amount, expires_at });
+    update_expiries(e, expires_at, true);
+    store_holder(e, supply, addr.clone());
+    e.storage().instance().set(&SUPPLY, &(supply + amount));
+    store_holder_since(e, addr);
+    amount
+}
+
+/// Transfers and burns move the single token an address can hold
+fn check_amount(amount: i128) -> Result<(), Error> {
+    if amount != 1 {
+        return Err(Error::InvalidAmount);
+    }
+
+    Ok(())
+}
+
+fn move_token(e: &Env, from: Address, to: Address) -> Result<(), Error> {
+    if get_balance(e, from.clone()) == 0 {
+        return Err(Error::AddressDoesNotHoldToken);
+    }
+
+    if get_holding(e, to.clone()).is_some() {
+        return Err(Error::AddressAlreadyHoldsToken);
+    }
+
+    // The token keeps its expiration when it changes hands
+    let holding = get_holding(e, from.clone()).unwrap();
+    let index = get_holder_index(e, from.clone());
+    e.storage().persistent().remove(&DataKey::Balance(from.clone()));
+    e.storage().persistent().remove(&DataKey::HolderSince(from.clone()));
+    e.storage().persistent().remove(&DataKey::HolderIndex(from));
+    store_holder(e, index, to.clone());
+    store_holding(e, to.clone(), &holding);
+    store_holder_since(e, to);
+    Ok(())
+}
+
+/// Removes the token held by the address, even an expired one, returning whether there was one
+fn remove_token(e: &Env, addr: Address) -> bool {
+    let holding = match get_holding(e, addr.clone()) {
+        Some(h) => h,
+        None => return false
+    };
+
+    let b = holding.amount;
+    if b > 0 {
+        update_expiries(e, holding.expires_at, false);
+        e.storage().persistent().remove(&DataKey::Balance(addr.clone()));
+        e.storage().persistent().remove(&DataKey::HolderSince(addr.clone()));
+        remove_holder(e, addr);
+        e.storage().instance().set(&SUPPLY, &(get_supply(e) - b));
+    }
+
+    b > 0
+}
+
+fn get_allowance(e: &Env, from: Address, spender: Address) -> i128 {
+    let key = DataKey::Allowance(AllowanceKey { from, spender });
+    match e.storage().temporary().get::<_, AllowanceValue>(&key) {
+        Some(a) if a.expiration_ledger >= e.ledger().sequence() => a.amount,
+        _ => 0
+    }
+}
+
+fn spend_allowance(e: &Env, from: Address, spender: Address, amount: i128) -> Result<(), Error> {
+    let allowance = get_allowance(e, from.clone(), spender.clone());
+    if allowance < amount {
+        return Err(Error::InsufficientAllowance);
+    }
+
+    let key = DataKey::Allowance(AllowanceKey { from, spender });
+    let mut value: AllowanceValue = e.storage().temporary().get(&key).unwrap();
+    value.amount = allowance - amount;
+    e.storage().temporary().set(&key, &value);
+    Ok(())
+}
 
 #[contract]
 pub struct BallotToken;
@@ -72,23 +452,31 @@ pub struct BallotToken;
 #[contractimpl]
 impl BallotToken {
 
-    pub fn initialize(e: Env, admin: Address) -> Result<bool, Error> {
+    pub fn initialize(e: Env, admin: Address, policy: TransferPolicy) -> Result<bool, Error> {
 
         if has_admin(&e) {
             return Err(Error::TokenAlreadyInitialized);
         }
 
         e.storage().instance().set(&TOKEN_ADMIN, &admin);
+        e.storage().instance().set(&POLICY, &policy);
         Ok(true)
         
     }
 
-    pub fn mint(e: Env, addr: Address) -> Result<u32, Error> {
+    /// Mints the token of the address, which stops counting from `expires_at` when given. Addresses whose
+    /// token expired keep it until it is renewed or clawed back.
+    pub fn mint(e: Env, addr: Address, expires_at: Option<u64>) -> Result<u32, Error> {
         
         let admin: Address = e.storage().instance().get(&TOKEN_ADMIN).unwrap();
         admin.require_auth();
 
-        if get_balance(&e, addr.clone()) > 0 {
+        if get_retired(&e).is_some() {
+            return Err(Error::TokenRetired);
+        }
+
+        let expires_at = check_expiration(&e, expires_at)?;
+        if get_holding(&e, addr.clone()).is_some() {
             return Err(Error::AddressAlreadyHoldsToken);
         }
 
@@ -96,119 +484,339 @@ impl BallotToken {
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
-        let key = DataKey::Balance(addr.clone());
-        let amount: u32 = 1;
-        e.storage().persistent().set(&key, &amount);
+        let amount = mint_token(&e, addr.clone(), expires_at);
+        events::mint(&e, admin, addr, amount as i128);
         Ok(amount)
     }
 
-    pub fn balance(e: Env, addr: Address) -> u32 {
+    /// Mints a token for every address with a single admin signature. Addresses already holding a token are
+    /// skipped and reported instead of failing the whole batch.
+    pub fn mint_batch(e: Env, addrs: Vec<Address>, expires_at: Option<u64>) -> Result<Vec<BatchOutcome>, Error> {
+        let admin: Address = e.storage().instance().get(&TOKEN_ADMIN).unwrap();
+        admin.require_auth();
+
+        if get_retired(&e).is_some() {
+            return Err(Error::TokenRetired);
+        }
+
+        let expires_at = check_expiration(&e, expires_at)?;
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        let mut outcomes: Vec<BatchOutcome> = Vec::new(&e);
+        for addr in addrs.iter() {
+            if get_holding(&e, addr.clone()).is_some() {
+                outcomes.push_back(BatchOutcome::AlreadyHoldsToken);
+                continue;
+            }
+
+            let amount = mint_token(&e, addr.clone(), expires_at);
+            events::mint(&e, admin.clone(), addr, amount as i128);
+            outcomes.push_back(BatchOutcome::Minted);
+        }
+
+        Ok(outcomes)
+    }
+
+    /// Revokes the token of every address with a single admin signature, reporting the addresses that did
+    /// not hold one.
+    pub fn burn_batch(e: Env, addrs: Vec<Address>) -> Vec<BatchOutcome> {
+        let admin: Address = e.storage().instance().get(&TOKEN_ADMIN).unwrap();
+        admin.require_auth();
+
         e.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
-        let b: u32 = get_balance(&e, addr);
-        b
+        let mut outcomes: Vec<BatchOutcome> = Vec::new(&e);
+        for addr in addrs.iter() {
+            if remove_token(&e, addr.clone()) {
+                events::clawback(&e, admin.clone(), addr, 1);
+                outcomes.push_back(BatchOutcome::Burned);
+            } else {
+                outcomes.push_back(BatchOutcome::DoesNotHoldToken);
+            }
+        }
+
+        outcomes
     }
 
-    pub fn transfer(e: Env, from: Address, to: Address) -> Result<bool, Error> {
-        from.require_auth();
+    /// Moves the expiration of the token held by the address, which counts again if it had expired
+    pub fn renew(e: Env, addr: Address, new_expiry: u64) -> Result<(), Error> {
+        let admin: Address = e.storage().instance().get(&TOKEN_ADMIN).unwrap();
+        admin.require_auth();
 
-        if get_balance(&e, from.clone()) == 0 {
-            return Err(Error::AddressDoesNotHoldToken);
+        if get_retired(&e).is_some() {
+            return Err(Error::TokenRetired);
         }
 
-        if get_balance(&e, to.clone()) > 0 {
-            return Err(Error::AddressAlreadyHoldsToken);
+        let expires_at = check_expiration(&e, Some(new_expiry))?;
+        let mut holding = match get_holding(&e, addr.clone()) {
+            Some(h) => h,
+            None => return Err(Error::AddressDoesNotHoldToken)
+        };
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        update_expiries(&e, holding.expires_at, false);
+        update_expiries(&e, expires_at, true);
+        holding.expires_at = expires_at;
+        store_holding(&e, addr.clone(), &holding);
+        events::renew(&e, admin, addr, expires_at);
+        Ok(())
+    }
+
+    /// Admin removal of the token held by the address
+    pub fn clawback(e: Env, addr: Address) {
+        let admin: Address = e.storage().instance().get(&TOKEN_ADMIN).unwrap();
+        admin.require_auth();
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        if remove_token(&e, addr.clone()) {
+            events::clawback(&e, admin, addr, 1);
         }
+    }
 
+    /// Timestamp at which the address acquired its token, 0 when it does not hold any
+    pub fn holder_since(e: Env, addr: Address) -> u64 {
         e.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
-        let from_key = DataKey::Balance(from.clone());
-        let to_key   = DataKey::Balance(to.clone());
-        let amount: u32 = 1;
+        get_holder_since(&e, addr)
+    }
+
+    /// Retires the token once its ballot is over, no more tokens can be minted. Burning voids every balance
+    /// at once, otherwise holders keep their tokens as a record of their participation. Unlocking makes the
+    /// kept tokens freely transferable, whatever the transfer policy they were issued under.
+    pub fn retire(e: Env, burn: bool, unlock: bool) -> Result<bool, Error> {
+        let admin: Address = e.storage().instance().get(&TOKEN_ADMIN).unwrap();
+        admin.require_auth();
+
+        if get_retired(&e).is_some() {
+            return Err(Error::TokenRetired);
+        }
 
-        e.storage().persistent().remove(&from_key); // from toklen is burned
-        e.storage().persistent().set(&to_key, &amount);
         e.storage()
-            .persistent()
-            .extend_ttl(&to_key, BALANCE_LIFETIME_THRESHOLD, BALANCE_BUMP_AMOUNT);
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        e.storage().instance().set(&RETIRED, &burn);
+        if burn {
+            e.storage().instance().set(&SUPPLY, &0_u32);
+        }
+
+        if unlock {
+            e.storage().instance().set(&POLICY, &TransferPolicy::Transferable);
+        }
 
         Ok(true)
     }
 
-    pub fn approve(e: Env, from: Address, spender: Address, expiration: u32) -> Result<bool, Error> {
-        from.require_auth();
-        if expiration < e.ledger().sequence(){
-            return Err(Error::ExpirationLedgerLessThanCurrentLedger);
+    pub fn transfer_policy(e: Env) -> TransferPolicy {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        get_policy(&e)
+    }
+
+    /// Tokens currently held, including expired tokens that were neither renewed nor clawed back. Use
+    /// `live_supply` to leave them out.
+    pub fn total_supply(e: Env) -> u32 {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        get_supply(&e)
+    }
+
+    /// Tokens currently held that have not expired by the timestamp `at`
+    pub fn live_supply(e: Env, at: u64) -> u32 {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        if is_burned(&e) {
+            return 0;
         }
 
-        if get_blocking(&e, from.clone()) {
-            return Err(Error::AddressAlreadyHasAllowance);
+        get_live_supply(&e, at)
+    }
+
+    /// Page of the current holders. Burning or transferring a token may change the position of other holders,
+    /// so pages should be read while no tokens move. Pages are capped to `MAX_PAGE_SIZE` addresses.
+    pub fn holders(e: Env, offset: u32, limit: u32) -> Vec<Address> {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        let total = get_supply(&e);
+        let start = offset.min(total);
+        let end = start.saturating_add(limit.min(MAX_PAGE_SIZE)).min(total);
+
+        let mut holders: Vec<Address> = Vec::new(&e);
+        for i in start..end {
+            holders.push_back(get_holder(&e, i));
         }
 
-        if get_allowance(&e, spender.clone()) {
-            return Err(Error::AddressAlreadyHasAllowance);
+        holders
+    }
+
+    /// Holders within the page of the holders index given by `offset` and `limit` whose token expires between
+    /// `from` and `to`, both included. Reminders are gathered by reading the index page after page.
+    pub fn expiring_between(e: Env, from: u64, to: u64, offset: u32, limit: u32) -> Vec<Address> {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        let total = get_supply(&e);
+        let start = offset.min(total);
+        let end = start.saturating_add(limit.min(MAX_PAGE_SIZE)).min(total);
+
+        let mut holders: Vec<Address> = Vec::new(&e);
+        for i in start..end {
+            let addr = get_holder(&e, i);
+            let holding = get_holding(&e, addr.clone()).unwrap();
+            if holding.expires_at > 0 && holding.expires_at >= from && holding.expires_at <= to {
+                holders.push_back(addr);
+            }
         }
 
-        if get_balance(&e, from.clone()) < 1 {
-            return Err(Error::AddressDoesNotHoldToken);
+        holders
+    }
+
+}
+
+/// SEP-41 token interface. An address holds at most one token, so balances are 0 or 1 and transfers and burns
+/// move exactly one token: any other amount fails with `InvalidAmount`, a recipient already holding a token
+/// with `AddressAlreadyHoldsToken` and allowances can only be 0 or 1. Failures are returned as contract errors,
+/// which is how standard token clients see them.
+#[contractimpl]
+impl BallotToken {
+
+    pub fn allowance(e: Env, from: Address, spender: Address) -> i128 {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        get_allowance(&e, from, spender)
+    }
+
+    pub fn approve(e: Env, from: Address, spender: Address, amount: i128, expiration_ledger: u32) -> Result<(), Error> {
+        from.require_auth();
+
+        if !(0..=1).contains(&amount) {
+            return Err(Error::InvalidAmount);
         }
 
-        if get_balance(&e, spender.clone()) < 1 {
-            return Err(Error::AddressDoesNotHoldToken);
+        if amount > 0 && expiration_ledger < e.ledger().sequence() {
+            return Err(Error::ExpirationLedgerLessThanCurrentLedger);
         }
 
-        let allowance_key = DataKey::Allowance(spender.clone());
-        let blocking_key  = DataKey::Blocking(from.clone());
-        e.storage().temporary().set(&allowance_key, &from);
-        e.storage().temporary().set(&blocking_key, &spender);
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
-        let live_for = expiration
-            .checked_sub(e.ledger().sequence())
-            .unwrap()
-        ;
+        let key = DataKey::Allowance(AllowanceKey { from: from.clone(), spender: spender.clone() });
+        e.storage().temporary().set(&key, &AllowanceValue { amount, expiration_ledger });
+        if amount > 0 {
+            let live_for = expiration_ledger - e.ledger().sequence();
+            e.storage().temporary().extend_ttl(&key, live_for, live_for);
+        }
 
-        e.storage().temporary().extend_ttl(&allowance_key, live_for, live_for);
-        e.storage().temporary().extend_ttl(&blocking_key, live_for, live_for);
+        events::approve(&e, from, spender, amount, expiration_ledger);
+        Ok(())
+    }
 
-        Ok(true)
+    pub fn balance(e: Env, id: Address) -> i128 {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        get_balance(&e, id) as i128
     }
 
-    pub fn allowance(e: &Env, addr: Address) -> bool {
+    pub fn transfer(e: Env, from: Address, to: Address, amount: i128) -> Result<(), Error> {
+        from.require_auth();
+        check_amount(amount)?;
+        check_transfer(&e)?;
 
         e.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
-        let allowance = get_allowance(&e, addr);
-        allowance
+        move_token(&e, from.clone(), to.clone())?;
+        events::transfer(&e, from, to, amount);
+        Ok(())
     }
 
-    pub fn blocking(e: &Env, addr: Address) -> bool {
+    pub fn transfer_from(e: Env, spender: Address, from: Address, to: Address, amount: i128) -> Result<(), Error> {
+        spender.require_auth();
+        check_amount(amount)?;
+        check_transfer(&e)?;
 
         e.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
-        let blocking = get_blocking(e, addr);
-        blocking
+        spend_allowance(&e, from.clone(), spender, amount)?;
+        move_token(&e, from.clone(), to.clone())?;
+        events::transfer(&e, from, to, amount);
+        Ok(())
     }
 
-    pub fn burn(e: Env, addr: Address) {
-        let admin: Address = e.storage().instance().get(&TOKEN_ADMIN).unwrap();
-        admin.require_auth();
+    pub fn burn(e: Env, from: Address, amount: i128) -> Result<(), Error> {
+        from.require_auth();
+        check_amount(amount)?;
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        if !remove_token(&e, from.clone()) {
+            return Err(Error::AddressDoesNotHoldToken);
+        }
+
+        events::burn(&e, from, amount);
+        Ok(())
+    }
+
+    pub fn burn_from(e: Env, spender: Address, from: Address, amount: i128) -> Result<(), Error> {
+        spender.require_auth();
+        check_amount(amount)?;
 
         e.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
-        let from_key = DataKey::Balance(addr);
-        e.storage().persistent().remove(&from_key);
+        spend_allowance(&e, from.clone(), spender, amount)?;
+        if !remove_token(&e, from.clone()) {
+            return Err(Error::AddressDoesNotHoldToken);
+        }
+
+        events::burn(&e, from, amount);
+        Ok(())
+    }
+
+    pub fn decimals(_e: Env) -> u32 {
+        DECIMALS
+    }
+
+    pub fn name(e: Env) -> String {
+        String::from_str(&e, NAME)
     }
 
+    pub fn symbol(e: Env) -> String {
+        String::from_str(&e, SYMBOL)
+    }
 }
 
 mod test;
@@ -1,15 +1,16 @@
 #![cfg(test)]
 
-use super::{BallotToken, BallotTokenClient};
-use soroban_sdk::{Env, Address, testutils::{Address as _, Ledger}};
+use super::{BallotToken, BallotTokenClient, BatchOutcome, Error, TransferPolicy, DAY_IN_SECONDS, LAST_EXPIRY_DAY};
+use soroban_sdk::{symbol_short, token::TokenClient, vec, Env, Address, IntoVal, String, testutils::{Address as _, Events, Ledger}};
 
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 fn initialize() {
     let env = Env::default();
     let client = create_client(&env);
 
     let admin = Address::generate(&env);
-    assert_eq!(client.initialize(&admin), true);
+    assert_eq!(client.initialize(&admin, &TransferPolicy::Transferable), true);
 }
 
 #[test]
@@ -19,8 +20,8 @@ fn already_initialized() {
     let client = create_client(&env);
 
     let admin = Address::generate(&env);
-    client.initialize(&admin);
-    client.initialize(&admin);
+    client.initialize(&admin, &TransferPolicy::Transferable);
+    client.initialize(&admin, &TransferPolicy::Transferable);
 }
 
 #[test]
@@ -31,8 +32,8 @@ fn mint() {
     let admin = Address::generate(&env);
     let to = Address::generate(&env);
 
-    client.initialize(&admin);
-    assert_eq!(client.mint(&to), 1);
+    client.initialize(&admin, &TransferPolicy::Transferable);
+    assert_eq!(client.mint(&to, &None), 1);
     assert_eq!(client.balance(&to), 1);
 }
 
@@ -45,9 +46,160 @@ fn already_minted() {
     let admin = Address::generate(&env);
     let to = Address::generate(&env);
 
-    client.initialize(&admin);
-    client.mint(&to);
-    client.mint(&to);
+    client.initialize(&admin, &TransferPolicy::Transferable);
+    client.mint(&to, &None);
+    client.mint(&to, &None);
+}
+
+#[test]
+fn mint_with_expiration() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1000;});
+    let client = create_client(&env);
+    let admin = Address::generate(&env);
+    let holder = Address::generate(&env);
+    let to = Address::generate(&env);
+
+    client.initialize(&admin, &TransferPolicy::Transferable);
+    assert_eq!(client.try_mint(&holder, &Some(1000)), Err(Ok(Error::InvalidExpiration)));
+    client.mint(&holder, &Some(2000));
+    assert_eq!(client.balance(&holder), 1);
+
+    env.ledger().with_mut(|l| {l.timestamp = 2000;});
+    assert_eq!(client.balance(&holder), 0);
+    assert_eq!(client.total_supply(), 1);
+    assert_eq!(client.try_transfer(&holder, &to, &1), Err(Ok(Error::AddressDoesNotHoldToken)));
+    assert_eq!(client.try_mint(&holder, &None), Err(Ok(Error::AddressAlreadyHoldsToken)));
+}
+
+#[test]
+fn renew() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1000;});
+    let client = create_client(&env);
+    let admin = Address::generate(&env);
+    let holder = Address::generate(&env);
+    let to = Address::generate(&env);
+
+    client.initialize(&admin, &TransferPolicy::Transferable);
+    client.mint(&holder, &Some(2000));
+    assert_eq!(client.try_renew(&to, &3000), Err(Ok(Error::AddressDoesNotHoldToken)));
+
+    env.ledger().with_mut(|l| {l.timestamp = 2500;});
+    assert_eq!(client.try_renew(&holder, &2500), Err(Ok(Error::InvalidExpiration)));
+    client.renew(&holder, &3000);
+    assert_eq!(client.balance(&holder), 1);
+
+    let events = env.events().all();
+    assert_eq!(
+        events.slice(events.len() - 1..),
+        vec![&env, (client.address.clone(), (symbol_short!("renew"), admin, holder.clone()).into_val(&env), 3000_u64.into_val(&env))]
+    );
+
+    // The expiration goes along with the token
+    client.transfer(&holder, &to, &1);
+    env.ledger().with_mut(|l| {l.timestamp = 3000;});
+    assert_eq!(client.balance(&to), 0);
+}
+
+#[test]
+fn live_supply() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1000;});
+    let client = create_client(&env);
+    let admin = Address::generate(&env);
+    let addr1 = Address::generate(&env);
+    let addr2 = Address::generate(&env);
+    let addr3 = Address::generate(&env);
+    let addr4 = Address::generate(&env);
+
+    client.initialize(&admin, &TransferPolicy::Transferable);
+    client.mint_batch(&vec![&env, addr1.clone(), addr2.clone()], &Some(2000));
+    client.mint(&addr3, &Some(3000));
+    client.mint(&addr4, &None);
+    assert_eq!(client.live_supply(&1999), 4);
+    assert_eq!(client.live_supply(&2000), 2);
+    assert_eq!(client.live_supply(&3000), 1);
+
+    client.renew(&addr1, &3000);
+    client.clawback(&addr2);
+    assert_eq!(client.live_supply(&2500), 3);
+    assert_eq!(client.live_supply(&3000), 1);
+    assert_eq!(client.total_supply(), 3);
+
+    client.retire(&true, &false);
+    assert_eq!(client.live_supply(&0), 0);
+}
+
+#[test]
+fn live_supply_at_scale() {
+    let env = Env::default();
+    let now = 1_700_000_000;
+    env.ledger().with_mut(|l| {l.timestamp = now;});
+    let small = create_client(&env);
+    let large = create_client(&env);
+    let admin = Address::generate(&env);
+    let holder = Address::generate(&env);
+
+    small.initialize(&admin, &TransferPolicy::Transferable);
+    large.initialize(&admin, &TransferPolicy::Transferable);
+    small.mint(&holder, &Some(now + DAY_IN_SECONDS));
+    large.mint(&holder, &Some(now + DAY_IN_SECONDS));
+
+    // Every token expires on its own day, a little later in the day each time
+    env.budget().reset_unlimited();
+    for i in 1..300 {
+        large.mint(&Address::generate(&env), &Some(now + (i + 1) * DAY_IN_SECONDS + i));
+    }
+
+    let at = now + 100 * DAY_IN_SECONDS + 50;
+    env.budget().reset_default();
+    small.balance(&holder);
+    let balance_cost = env.budget().cpu_instruction_cost();
+    env.budget().reset_default();
+    small.live_supply(&at);
+    let live_supply_cost = env.budget().cpu_instruction_cost();
+
+    env.budget().reset_default();
+    large.balance(&holder);
+    assert!(env.budget().cpu_instruction_cost() < balance_cost * 11 / 10);
+    env.budget().reset_default();
+    assert_eq!(large.live_supply(&at), 201);
+    assert!(env.budget().cpu_instruction_cost() < live_supply_cost * 3 / 2);
+
+    env.budget().reset_unlimited();
+    assert_eq!(large.live_supply(&(at + 49)), 200);
+    assert_eq!(large.live_supply(&u64::MAX), 0);
+
+    let too_late = (LAST_EXPIRY_DAY + 1) * DAY_IN_SECONDS;
+    assert_eq!(large.try_mint(&Address::generate(&env), &Some(too_late)), Err(Ok(Error::InvalidExpiration)));
+    large.mint(&Address::generate(&env), &Some(too_late - 1));
+    assert_eq!(large.live_supply(&(too_late - 1)), 0);
+    assert_eq!(large.live_supply(&(too_late - 2)), 1);
+}
+
+#[test]
+fn expiring_between() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let admin = Address::generate(&env);
+    let addr1 = Address::generate(&env);
+    let addr2 = Address::generate(&env);
+    let addr3 = Address::generate(&env);
+    let addr4 = Address::generate(&env);
+
+    client.initialize(&admin, &TransferPolicy::Transferable);
+    client.mint(&addr1, &Some(1000));
+    client.mint(&addr2, &None);
+    client.mint(&addr3, &Some(2000));
+    client.mint(&addr4, &Some(3000));
+
+    assert_eq!(client.expiring_between(&1000, &2000, &0, &10), vec![&env, addr1.clone(), addr3.clone()]);
+    assert_eq!(client.expiring_between(&1000, &2000, &1, &2), vec![&env, addr3]);
+    assert_eq!(client.expiring_between(&0, &u64::MAX, &3, &10), vec![&env, addr4]);
+
+    client.burn_batch(&vec![&env, addr1]);
+    assert_eq!(client.expiring_between(&0, &1500, &0, &10), vec![&env]);
 }
 
 #[test]
@@ -58,9 +210,9 @@ fn transfer() {
     let from = Address::generate(&env);
     let to = Address::generate(&env);
 
-    client.initialize(&admin);
-    client.mint(&from);
-    client.transfer(&from, &to);
+    client.initialize(&admin, &TransferPolicy::Transferable);
+    client.mint(&from, &None);
+    client.transfer(&from, &to, &1);
 
     assert_eq!(client.balance(&from), 0);
     assert_eq!(client.balance(&to), 1);
@@ -68,7 +220,6 @@ fn transfer() {
 }
 
 #[test]
-#[should_panic(expected = "HostError: Error(Contract, #3)")]
 fn transfer_from_does_not_hold_token() {
     let env = Env::default();
     let client = create_client(&env);
@@ -76,12 +227,11 @@ fn transfer_from_does_not_hold_token() {
     let from = Address::generate(&env);
     let to = Address::generate(&env);
 
-    client.initialize(&admin);
-    client.transfer(&from, &to);
+    client.initialize(&admin, &TransferPolicy::Transferable);
+    assert_eq!(client.try_transfer(&from, &to, &1), Err(Ok(Error::AddressDoesNotHoldToken)));
 }
 
 #[test]
-#[should_panic(expected = "HostError: Error(Contract, #2)")]
 fn transfer_to_already_holds_token() {
     let env = Env::default();
     let client = create_client(&env);
@@ -89,55 +239,77 @@ fn transfer_to_already_holds_token() {
     let from = Address::generate(&env);
     let to = Address::generate(&env);
 
-    client.initialize(&admin);
-    client.mint(&from);
-    client.mint(&to);
-    client.transfer(&from, &to);
+    client.initialize(&admin, &TransferPolicy::Transferable);
+    client.mint(&from, &None);
+    client.mint(&to, &None);
+    assert_eq!(client.try_transfer(&from, &to, &1), Err(Ok(Error::AddressAlreadyHoldsToken)));
 }
 
 #[test]
-fn no_allowance() {
+fn transfer_more_than_one_token() {
     let env = Env::default();
     let client = create_client(&env);
     let admin = Address::generate(&env);
-    let addr = Address::generate(&env);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
 
-    client.initialize(&admin);
-    assert_eq!(client.allowance(&addr), false);
+    client.initialize(&admin, &TransferPolicy::Transferable);
+    client.mint(&from, &None);
+    assert_eq!(client.try_transfer(&from, &to, &2), Err(Ok(Error::InvalidAmount)));
 }
 
 #[test]
-fn no_blocking() {
+fn no_allowance() {
     let env = Env::default();
     let client = create_client(&env);
     let admin = Address::generate(&env);
-    let addr = Address::generate(&env);
+    let from = Address::generate(&env);
+    let spender = Address::generate(&env);
 
-    client.initialize(&admin);
-    assert_eq!(client.blocking(&addr), false);
+    client.initialize(&admin, &TransferPolicy::Transferable);
+    assert_eq!(client.allowance(&from, &spender), 0);
 }
 
 #[test]
-fn approve_and_allowance() {
+fn approve_and_transfer_from() {
     let env = Env::default();
     let client = create_client(&env);
     let admin = Address::generate(&env);
     let from = Address::generate(&env);
     let spender = Address::generate(&env);
+    let to = Address::generate(&env);
 
     env.ledger().with_mut(|li| {li.sequence_number = 2499;});
 
-    client.initialize(&admin);
-    client.mint(&from);
-    client.mint(&spender);
+    client.initialize(&admin, &TransferPolicy::Transferable);
+    client.mint(&from, &None);
+
+    client.approve(&from, &spender, &1, &2500);
+    assert_eq!(client.allowance(&from, &spender), 1);
 
-    client.approve(&from, &spender, &2500);
-    assert_eq!(client.allowance(&spender), true);
-    assert_eq!(client.blocking(&from), true);
+    client.transfer_from(&spender, &from, &to, &1);
+    assert_eq!(client.allowance(&from, &spender), 0);
+    assert_eq!(client.balance(&from), 0);
+    assert_eq!(client.balance(&to), 1);
+}
+
+#[test]
+fn allowance_expires() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let admin = Address::generate(&env);
+    let from = Address::generate(&env);
+    let spender = Address::generate(&env);
+
+    env.ledger().with_mut(|li| {li.sequence_number = 2499;});
+    client.initialize(&admin, &TransferPolicy::Transferable);
+    client.approve(&from, &spender, &1, &2500);
+
+    env.ledger().with_mut(|li| {li.sequence_number = 2501;});
+    assert_eq!(client.allowance(&from, &spender), 0);
 }
 
 #[test]
-#[should_panic(expected = "HostError: Error(Contract, #5)")]
 fn approve_expiration_ledger_lower_than_current() {
     let env = Env::default();
     let client = create_client(&env);
@@ -146,16 +318,14 @@ fn approve_expiration_ledger_lower_than_current() {
     let spender = Address::generate(&env);
 
     env.ledger().with_mut(|li| {li.sequence_number = 2499;});
-    client.initialize(&admin);
-    client.mint(&from);
-    client.mint(&spender);
+    client.initialize(&admin, &TransferPolicy::Transferable);
+    client.mint(&from, &None);
 
-    client.approve(&from, &spender, &2498);
+    assert_eq!(client.try_approve(&from, &spender, &1, &2498), Err(Ok(Error::ExpirationLedgerLessThanCurrentLedger)));
 }
 
 #[test]
-#[should_panic(expected = "HostError: Error(Contract, #4)")]
-fn approve_from_already_has_allowance() {
+fn approve_more_than_one_token() {
     let env = Env::default();
     let client = create_client(&env);
     let admin = Address::generate(&env);
@@ -163,69 +333,346 @@ fn approve_from_already_has_allowance() {
     let spender = Address::generate(&env);
     env.ledger().with_mut(|li| {li.sequence_number = 2499;});
 
-    client.initialize(&admin);
-    client.mint(&from);
-    client.mint(&spender);
+    client.initialize(&admin, &TransferPolicy::Transferable);
+    client.mint(&from, &None);
 
-    client.approve(&from, &spender, &2500);
-    client.approve(&from, &spender, &2500);
+    assert_eq!(client.try_approve(&from, &spender, &2, &2500), Err(Ok(Error::InvalidAmount)));
 }
 
 #[test]
-#[should_panic(expected = "HostError: Error(Contract, #4)")]
-fn approve_spender_already_has_allowance() {
+fn transfer_from_without_allowance() {
     let env = Env::default();
     let client = create_client(&env);
     let admin = Address::generate(&env);
-    let from1 = Address::generate(&env);
-    let from2 = Address::generate(&env);
+    let from = Address::generate(&env);
+    let spender = Address::generate(&env);
+    let to = Address::generate(&env);
+
+    client.initialize(&admin, &TransferPolicy::Transferable);
+    client.mint(&from, &None);
+    assert_eq!(client.try_transfer_from(&spender, &from, &to, &1), Err(Ok(Error::InsufficientAllowance)));
+}
+
+#[test]
+fn burn_and_burn_from() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let admin = Address::generate(&env);
+    let holder = Address::generate(&env);
+    let other = Address::generate(&env);
     let spender = Address::generate(&env);
     env.ledger().with_mut(|li| {li.sequence_number = 2499;});
 
-    client.initialize(&admin);
-    client.mint(&from1);
-    client.mint(&from2);
-    client.mint(&spender);
+    client.initialize(&admin, &TransferPolicy::Transferable);
+    client.mint(&holder, &None);
+    client.mint(&other, &None);
+
+    client.burn(&holder, &1);
+    assert_eq!(client.balance(&holder), 0);
+    assert_eq!(client.try_burn(&holder, &1), Err(Ok(Error::AddressDoesNotHoldToken)));
+
+    client.approve(&other, &spender, &1, &2500);
+    client.burn_from(&spender, &other, &1);
+    assert_eq!(client.balance(&other), 0);
+    assert_eq!(client.total_supply(), 0);
+}
+
+#[test]
+fn metadata() {
+    let env = Env::default();
+    let client = create_client(&env);
 
-    client.approve(&from1, &spender, &2500);
-    client.approve(&from2, &spender, &2500);
+    assert_eq!(client.decimals(), 0);
+    assert_eq!(client.name(), String::from_str(&env, "Ballot Token"));
+    assert_eq!(client.symbol(), String::from_str(&env, "BALLOT"));
 }
 
 #[test]
-#[should_panic(expected = "HostError: Error(Contract, #3)")]
-fn approve_from_does_not_hold_token() {
+fn transfer_event() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let admin = Address::generate(&env);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+
+    client.initialize(&admin, &TransferPolicy::Transferable);
+    client.mint(&from, &None);
+    client.transfer(&from, &to, &1);
+
+    let events = env.events().all();
+    assert_eq!(
+        events.slice(events.len() - 1..),
+        vec![
+            &env,
+            (
+                client.address.clone(),
+                (symbol_short!("transfer"), from, to).into_val(&env),
+                1_i128.into_val(&env)
+            )
+        ]
+    );
+}
+
+#[test]
+fn transfer_admin_approved() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let admin = Address::generate(&env);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+
+    client.initialize(&admin, &TransferPolicy::AdminApproved);
+    client.mint(&from, &None);
+    client.transfer(&from, &to, &1);
+
+    let auths = env.auths();
+    assert!(auths.iter().any(|(addr, _)| *addr == admin));
+    assert!(auths.iter().any(|(addr, _)| *addr == from));
+    assert_eq!(client.balance(&to), 1);
+    assert_eq!(client.transfer_policy(), TransferPolicy::AdminApproved);
+}
+
+#[test]
+fn transfer_soulbound() {
     let env = Env::default();
     let client = create_client(&env);
     let admin = Address::generate(&env);
     let from = Address::generate(&env);
     let spender = Address::generate(&env);
-    env.ledger().with_mut(|li| {li.sequence_number = 2499;});
+    let to = Address::generate(&env);
 
-    client.initialize(&admin);
-    client.mint(&spender);
+    client.initialize(&admin, &TransferPolicy::Soulbound);
+    client.mint(&from, &None);
+    client.approve(&from, &spender, &1, &200);
+    assert_eq!(client.try_transfer(&from, &to, &1), Err(Ok(Error::TransferNotAllowed)));
+    assert_eq!(client.try_transfer_from(&spender, &from, &to, &1), Err(Ok(Error::TransferNotAllowed)));
+    assert_eq!(client.balance(&from), 1);
 
-    client.approve(&from, &spender, &2500);
+    // Holders can still give up their token
+    client.burn(&from, &1);
+    assert_eq!(client.balance(&from), 0);
 }
 
 #[test]
-fn burn() {
+fn clawback() {
     let env = Env::default();
     let client = create_client(&env);
     let admin = Address::generate(&env);
     let addr = Address::generate(&env);
 
-    client.initialize(&admin);
-    client.mint(&addr);
+    client.initialize(&admin, &TransferPolicy::Transferable);
+    client.mint(&addr, &None);
     assert_eq!(client.balance(&addr), 1);
 
-    client.burn(&addr);
+    client.clawback(&addr);
+    assert_eq!(client.balance(&addr), 0);
+}
+
+#[test]
+fn total_supply() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let admin = Address::generate(&env);
+    let addr1 = Address::generate(&env);
+    let addr2 = Address::generate(&env);
+    let addr3 = Address::generate(&env);
+
+    client.initialize(&admin, &TransferPolicy::Transferable);
+    assert_eq!(client.total_supply(), 0);
+
+    client.mint(&addr1, &None);
+    client.mint(&addr2, &None);
+    client.transfer(&addr2, &addr3, &1);
+    assert_eq!(client.total_supply(), 2);
+
+    client.clawback(&addr1);
+    client.clawback(&addr1);
+    assert_eq!(client.total_supply(), 1);
+}
+
+#[test]
+fn holders() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let admin = Address::generate(&env);
+    let addr1 = Address::generate(&env);
+    let addr2 = Address::generate(&env);
+    let addr3 = Address::generate(&env);
+    let addr4 = Address::generate(&env);
+
+    client.initialize(&admin, &TransferPolicy::Transferable);
+    client.mint_batch(&vec![&env, addr1.clone(), addr2.clone(), addr3.clone()], &None);
+    assert_eq!(client.holders(&0, &10), vec![&env, addr1.clone(), addr2.clone(), addr3.clone()]);
+    assert_eq!(client.holders(&1, &1), vec![&env, addr2.clone()]);
+    assert_eq!(client.holders(&5, &10), vec![&env]);
+
+    client.transfer(&addr2, &addr4, &1);
+    client.clawback(&addr1);
+    assert_eq!(client.holders(&0, &10), vec![&env, addr3, addr4]);
+
+    client.retire(&true, &false);
+    assert_eq!(client.holders(&0, &10), vec![&env]);
+}
+
+#[test]
+fn holder_since() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let admin = Address::generate(&env);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+
+    client.initialize(&admin, &TransferPolicy::Transferable);
+    env.ledger().with_mut(|l| {l.timestamp = 1689238000;});
+    client.mint(&from, &None);
+    assert_eq!(client.holder_since(&from), 1689238000);
+
+    env.ledger().with_mut(|l| {l.timestamp = 1689238900;});
+    client.transfer(&from, &to, &1);
+    assert_eq!(client.holder_since(&from), 0);
+    assert_eq!(client.holder_since(&to), 1689238900);
+
+    client.clawback(&to);
+    assert_eq!(client.holder_since(&to), 0);
+}
+
+#[test]
+fn retire_and_burn() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let admin = Address::generate(&env);
+    let addr = Address::generate(&env);
+    let to = Address::generate(&env);
+
+    client.initialize(&admin, &TransferPolicy::Transferable);
+    client.mint(&addr, &None);
+    client.retire(&true, &false);
     assert_eq!(client.balance(&addr), 0);
+    assert_eq!(client.holder_since(&addr), 0);
+    assert_eq!(client.total_supply(), 0);
+    assert_eq!(client.try_transfer(&addr, &to, &1), Err(Ok(Error::AddressDoesNotHoldToken)));
+    assert_eq!(client.try_retire(&false, &true), Err(Ok(Error::TokenRetired)));
+}
+
+#[test]
+fn retire_and_keep() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let admin = Address::generate(&env);
+    let addr = Address::generate(&env);
+    let to = Address::generate(&env);
+
+    client.initialize(&admin, &TransferPolicy::Transferable);
+    client.mint(&addr, &None);
+    client.retire(&false, &false);
+    assert_eq!(client.try_mint(&to, &None), Err(Ok(Error::TokenRetired)));
+
+    client.transfer(&addr, &to, &1);
+    assert_eq!(client.balance(&to), 1);
+    assert_eq!(client.total_supply(), 1);
+}
+
+#[test]
+fn retire_and_unlock() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let admin = Address::generate(&env);
+    let addr = Address::generate(&env);
+    let to = Address::generate(&env);
+
+    client.initialize(&admin, &TransferPolicy::Soulbound);
+    client.mint(&addr, &None);
+    assert_eq!(client.try_transfer(&addr, &to, &1), Err(Ok(Error::TransferNotAllowed)));
+
+    client.retire(&false, &true);
+    assert_eq!(client.transfer_policy(), TransferPolicy::Transferable);
+    assert_eq!(client.try_mint(&to, &None), Err(Ok(Error::TokenRetired)));
+    client.transfer(&addr, &to, &1);
+    assert_eq!(client.balance(&to), 1);
+}
+
+#[test]
+fn standard_token_client() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let admin = Address::generate(&env);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+
+    client.initialize(&admin, &TransferPolicy::Transferable);
+    client.mint(&from, &None);
+
+    let sep41 = TokenClient::new(&env, &client.address);
+    assert_eq!(sep41.balance(&from), 1);
+    assert_eq!(sep41.symbol(), String::from_str(&env, "BALLOT"));
+    sep41.transfer(&from, &to, &1);
+    assert_eq!(sep41.balance(&to), 1);
+}
+
+#[test]
+fn mint_batch() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let admin = Address::generate(&env);
+    let holder = Address::generate(&env);
+    let addr1 = Address::generate(&env);
+    let addr2 = Address::generate(&env);
+
+    client.initialize(&admin, &TransferPolicy::Transferable);
+    client.mint(&holder, &None);
+
+    let outcomes = client.mint_batch(&vec![&env, addr1.clone(), holder.clone(), addr2.clone(), addr1.clone()], &None);
+    assert_eq!(outcomes, vec![&env, BatchOutcome::Minted, BatchOutcome::AlreadyHoldsToken, BatchOutcome::Minted, BatchOutcome::AlreadyHoldsToken]);
+    assert_eq!(client.balance(&addr1), 1);
+    assert_eq!(client.balance(&addr2), 1);
+    assert_eq!(client.total_supply(), 3);
+
+    let events = env.events().all();
+    assert_eq!(
+        events.slice(events.len() - 2..),
+        vec![
+            &env,
+            (client.address.clone(), (symbol_short!("mint"), admin.clone(), addr1).into_val(&env), 1_i128.into_val(&env)),
+            (client.address.clone(), (symbol_short!("mint"), admin, addr2).into_val(&env), 1_i128.into_val(&env))
+        ]
+    );
+}
+
+#[test]
+fn burn_batch() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let admin = Address::generate(&env);
+    let addr1 = Address::generate(&env);
+    let addr2 = Address::generate(&env);
+    let outsider = Address::generate(&env);
+
+    client.initialize(&admin, &TransferPolicy::Transferable);
+    client.mint_batch(&vec![&env, addr1.clone(), addr2.clone()], &None);
+
+    let outcomes = client.burn_batch(&vec![&env, addr1.clone(), outsider, addr2.clone()]);
+    assert_eq!(outcomes, vec![&env, BatchOutcome::Burned, BatchOutcome::DoesNotHoldToken, BatchOutcome::Burned]);
+    assert_eq!(client.balance(&addr1), 0);
+    assert_eq!(client.holder_since(&addr2), 0);
+    assert_eq!(client.total_supply(), 0);
+    assert_eq!(env.events().all().len(), 4);
+}
+
+#[test]
+fn mint_batch_once_retired() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let admin = Address::generate(&env);
+
+    client.initialize(&admin, &TransferPolicy::Transferable);
+    client.retire(&false, &false);
+    assert_eq!(client.try_mint_batch(&vec![&env, Address::generate(&env)], &None), Err(Ok(Error::TokenRetired)));
 }
 
-fn create_client(env: &Env) -> BallotTokenClient{
+fn create_client(env: &Env) -> BallotTokenClient<'_>{
     env.mock_all_auths();
     let contract_id = env.register_contract(None, BallotToken);
-    let client = BallotTokenClient::new(&env, &contract_id);
+    let client = BallotTokenClient::new(env, &contract_id);
 
     client
 }
\ No newline at end of file
@@ -0,0 +1,31 @@
+use soroban_sdk::{symbol_short, Address, BytesN, Env, Map, Symbol};
+
+pub const BALLOT: Symbol = symbol_short!("ballot");
+
+pub fn configured(env: &Env, admin: Address, ts_start: u64, ts_end: u64, reveal_period: u64) {
+    env.events().publish((BALLOT, symbol_short!("configure"), admin), (ts_start, ts_end, reveal_period));
+}
+
+pub fn voted(env: &Env, voter: Symbol, candidate: Symbol, weight: u32) {
+    env.events().publish((BALLOT, symbol_short!("voted"), voter), (candidate, weight));
+}
+
+pub fn committed(env: &Env, voter: Symbol, commitment: BytesN<32>) {
+    env.events().publish((BALLOT, symbol_short!("committed"), voter), commitment);
+}
+
+pub fn revealed(env: &Env, voter: Symbol, candidate: Symbol, weight: u32) {
+    env.events().publish((BALLOT, symbol_short!("revealed"), voter), (candidate, weight));
+}
+
+pub fn abstained(env: &Env, voter: Symbol, weight: u32) {
+    env.events().publish((BALLOT, symbol_short!("abstained"), voter), weight);
+}
+
+pub fn delegated(env: &Env, o_voter: Symbol, d_voter: Symbol) {
+    env.events().publish((BALLOT, symbol_short!("delegated"), o_voter), d_voter);
+}
+
+pub fn counted(env: &Env, count: Map<Symbol, u32>) {
+    env.events().publish((BALLOT, symbol_short!("counted")), count);
+}
@@ -1,7 +1,8 @@
 #![no_std]
 
-use soroban_sdk::{contract, contractimpl, contracterror, Env, Symbol, Map, Address, Vec};
+use soroban_sdk::{contract, contractimpl, contracterror, xdr::ToXdr, BytesN, Env, Symbol, Map, Address, Vec};
 
+mod events;
 mod storage;
 mod validation;
 use storage::VCounter;
@@ -24,7 +25,7 @@ impl<'a> Voter<'a> {
 
     fn has_delegated_votes(&self, env: &Env) -> bool {
         let dvotes = storage::get_voter_delegated_votes(env, self.id);
-        if dvotes.len() > 0 {
+        if !dvotes.is_empty() {
             return true;
         }
     
@@ -36,12 +37,68 @@ fn check_dates(env: &Env) -> bool {
     let cfg = storage::get_config(env);
     let mut valid = true;
     if cfg.from > 0 && cfg.to > 0 {
-        valid = validation::is_valid_date(&env, &cfg.from, &cfg.to)
+        valid = validation::is_valid_date(env, &cfg.from, &cfg.to)
     }
 
     valid
 }
 
+fn check_admin(env: &Env, admin: &Address) -> Result<(), Error> {
+    admin.require_auth();
+    match storage::get_admin(env) {
+        Some(a) if a == *admin => Ok(()),
+        Some(_) => Err(Error::AddressIsNotAdmin),
+        None => Err(Error::BallotNotConfigured)
+    }
+}
+
+fn is_candidates_locked(env: &Env) -> bool {
+    let cfg = storage::get_config(env);
+    cfg.from > 0 && env.ledger().timestamp() >= cfg.from
+}
+
+/// Commit-reveal ballots end once their reveal period is over
+fn has_ended(env: &Env) -> bool {
+    let cfg = storage::get_config(env);
+    cfg.to > 0 && env.ledger().timestamp() > cfg.to + cfg.reveal_period
+}
+
+fn is_commit_reveal(env: &Env) -> bool {
+    storage::get_config(env).reveal_period > 0
+}
+
+fn is_revealing(env: &Env) -> bool {
+    let cfg = storage::get_config(env);
+    let now = env.ledger().timestamp();
+    cfg.reveal_period > 0 && now > cfg.to && now <= cfg.to + cfg.reveal_period
+}
+
+/// Commitment submitted for a candidate: `sha256(xdr(candidate) || salt)`
+fn commitment(env: &Env, candidate: &Symbol, salt: &BytesN<32>) -> BytesN<32> {
+    let mut data = candidate.clone().to_xdr(env);
+    data.extend_from_array(&salt.to_array());
+    env.crypto().sha256(&data)
+}
+
+/// Candidate counts together with the abstentions and the turnout. Commit-reveal ballots also report the
+/// commitments that were never revealed.
+fn tally_map(env: &Env) -> Map<Symbol, u32> {
+    let mut count_map: Map<Symbol, u32> = Map::new(env);
+    for party in storage::get_candidates(env).iter() {
+        let candidate_key = VCounter::Counter(party.clone());
+        let candidate_count: u32 = storage::get_candidate_votes_count(env, &candidate_key);
+        count_map.set(party, candidate_count);
+    }
+
+    count_map.set(storage::ABSTAIN, storage::get_candidate_votes_count(env, &VCounter::Counter(storage::ABSTAIN)));
+    count_map.set(storage::TURNOUT, storage::get_votes(env).len());
+    if is_commit_reveal(env) {
+        count_map.set(storage::SEALED, storage::get_candidate_votes_count(env, &VCounter::Counter(storage::SEALED)));
+    }
+
+    count_map
+}
+
 
 #[contracterror]
 #[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
@@ -52,8 +109,17 @@ pub enum Error {
     VoterHasDelegatedVotes = 3,
     VoterOriginHasAlreadyVotedAndCannotDelegate = 4,
     VoterTargetHasAlreadyVotedAndCannotDelegate = 5,
-    BallotOutOfDate = 6
-
+    BallotOutOfDate = 6,
+    InvalidCandidate = 7,
+    CandidatesLocked = 8,
+    BallotNotClosed = 9,
+    CommitRevealBallot = 10,
+    VoteNotCommitted = 11,
+    InvalidReveal = 12,
+    AddressIsNotAdmin = 13,
+    BallotNotConfigured = 14,
+    InvalidBallotMode = 15,
+    BallotAlreadyStarted = 16
 }
 
 #[contract]
@@ -62,15 +128,71 @@ pub struct Ballot;
 #[contractimpl]
 impl Ballot {
 
-    pub fn configure(env: Env, admin: Address, ts_start: u64, ts_end: u64) -> Result<bool, Error> {
-        admin.require_auth();
-        storage::store_config(&env, ts_start, ts_end);
+    /// A `reveal_period`, in seconds, makes votes be committed while the ballot is open and revealed during
+    /// that period once it closes. 0 keeps votes public. The first caller becomes the ballot admin, only that
+    /// address can configure the ballot again or manage its candidates. The reveal period cannot change once
+    /// the ballot has opened and votes were cast, as they would be neither revealed nor counted.
+    pub fn configure(env: Env, admin: Address, ts_start: u64, ts_end: u64, reveal_period: u64) -> Result<bool, Error> {
+        if storage::get_admin(&env).is_some() {
+            check_admin(&env, &admin)?;
+            let cast = is_candidates_locked(&env) && !storage::get_votes(&env).is_empty();
+            if cast && reveal_period != storage::get_config(&env).reveal_period {
+                return Err(Error::BallotAlreadyStarted);
+            }
+        } else {
+            admin.require_auth();
+            storage::store_admin(&env, &admin);
+        }
+
+        storage::store_config(&env, ts_start, ts_end, reveal_period);
+        events::configured(&env, admin, ts_start, ts_end, reveal_period);
+        Ok(true)
+    }
+
+    /// Registers the candidates voters can choose from. The list can only be edited before the voting window opens.
+    pub fn register_candidates(env: Env, admin: Address, candidates: Vec<Symbol>) -> Result<bool, Error> {
+        check_admin(&env, &admin)?;
+
+        if is_candidates_locked(&env) {
+            return Err(Error::CandidatesLocked);
+        }
+
+        if candidates.contains(storage::TURNOUT) || candidates.contains(storage::ABSTAIN) || candidates.contains(storage::SEALED) {
+            return Err(Error::InvalidCandidate);
+        }
+
+        for candidate in candidates.iter() {
+            storage::store_party(&env, &candidate);
+        }
+
         Ok(true)
     }
 
+    pub fn remove_candidate(env: Env, admin: Address, candidate: Symbol) -> Result<bool, Error> {
+        check_admin(&env, &admin)?;
+
+        if is_candidates_locked(&env) {
+            return Err(Error::CandidatesLocked);
+        }
+
+        if !storage::remove_party(&env, &candidate) {
+            return Err(Error::InvalidCandidate);
+        }
+
+        Ok(true)
+    }
+
+    pub fn candidates(env: Env) -> Vec<Symbol> {
+        storage::get_candidates(&env)
+    }
+
     pub fn vote(env: Env, admin: Address, voter: Symbol, candidate: Symbol) -> Result<bool, Error> {
         admin.require_auth();
-        
+
+        if is_commit_reveal(&env) {
+            return Err(Error::CommitRevealBallot);
+        }
+
         if !check_dates(&env) {
             return Err(Error::BallotOutOfDate);
         }
@@ -83,17 +205,124 @@ impl Ballot {
         if v.has_voted(&env) {
             return Err(Error::VoterHasAlreadyVoted)
         }
-        
-        storage::store_party(&env, &candidate);
+
+        if !storage::get_candidates(&env).contains(&candidate) {
+            return Err(Error::InvalidCandidate)
+        }
 
         let mut votes: Vec<Symbol> = storage::get_votes(&env);
-        let candidate_key = VCounter::Counter(candidate);
+        let candidate_key = VCounter::Counter(candidate.clone());
         let d_votes: Vec<Symbol> = storage::get_voter_delegated_votes(&env, v.id);
         let count = 1 + d_votes.len() + storage::get_candidate_votes_count(&env, &candidate_key);
-        votes.push_back(voter);
+        votes.push_back(voter.clone());
  
         storage::update_candidate_count(&env, candidate_key, count);
         storage::update_votes(&env, votes);
+        events::voted(&env, voter, candidate, 1 + d_votes.len());
+
+        Ok(true)
+    }
+
+    /// Casts a blank ballot for the voter. It counts toward turnout but not for any candidate.
+    pub fn abstain(env: Env, admin: Address, voter: Symbol) -> Result<bool, Error> {
+        check_admin(&env, &admin)?;
+
+        if is_commit_reveal(&env) {
+            return Err(Error::CommitRevealBallot);
+        }
+
+        if !check_dates(&env) {
+            return Err(Error::BallotOutOfDate);
+        }
+
+        let v: Voter = Voter { id: &voter };
+
+        if v.is_delegated(&env) {
+            return Err(Error::VoterHasHisVoteDelegated)
+        }
+        if v.has_voted(&env) {
+            return Err(Error::VoterHasAlreadyVoted)
+        }
+
+        let mut votes: Vec<Symbol> = storage::get_votes(&env);
+        let abstain_key = VCounter::Counter(storage::ABSTAIN);
+        let d_votes: Vec<Symbol> = storage::get_voter_delegated_votes(&env, v.id);
+        let count = 1 + d_votes.len() + storage::get_candidate_votes_count(&env, &abstain_key);
+        votes.push_back(voter.clone());
+
+        storage::update_candidate_count(&env, abstain_key, count);
+        storage::update_votes(&env, votes);
+        events::abstained(&env, voter, 1 + d_votes.len());
+
+        Ok(true)
+    }
+
+    /// Stores the sealed vote of the voter in a commit-reveal ballot. It counts toward turnout straight away
+    /// and for the candidate once revealed.
+    pub fn commit_vote(env: Env, admin: Address, voter: Symbol, commitment: BytesN<32>) -> Result<bool, Error> {
+        check_admin(&env, &admin)?;
+
+        if !is_commit_reveal(&env) {
+            return Err(Error::InvalidBallotMode);
+        }
+
+        if !check_dates(&env) {
+            return Err(Error::BallotOutOfDate);
+        }
+
+        let v: Voter = Voter { id: &voter };
+
+        if v.is_delegated(&env) {
+            return Err(Error::VoterHasHisVoteDelegated)
+        }
+        if v.has_voted(&env) {
+            return Err(Error::VoterHasAlreadyVoted)
+        }
+
+        let mut votes: Vec<Symbol> = storage::get_votes(&env);
+        let sealed_key = VCounter::Counter(storage::SEALED);
+        let sealed = 1 + storage::get_candidate_votes_count(&env, &sealed_key);
+        votes.push_back(voter.clone());
+
+        storage::update_candidate_count(&env, sealed_key, sealed);
+        storage::store_commitment(&env, &voter, &commitment);
+        storage::update_votes(&env, votes);
+        events::committed(&env, voter, commitment);
+
+        Ok(true)
+    }
+
+    /// Reveals a committed vote during the reveal period. The votes delegated to the voter are counted with it.
+    pub fn reveal_vote(env: Env, admin: Address, voter: Symbol, candidate: Symbol, salt: BytesN<32>) -> Result<bool, Error> {
+        check_admin(&env, &admin)?;
+
+        if !is_revealing(&env) {
+            return Err(Error::BallotOutOfDate);
+        }
+
+        let sealed = match storage::get_commitment(&env, &voter) {
+            Some(c) => c,
+            None => return Err(Error::VoteNotCommitted)
+        };
+
+        if sealed != commitment(&env, &candidate, &salt) {
+            return Err(Error::InvalidReveal);
+        }
+
+        if !storage::get_candidates(&env).contains(&candidate) {
+            return Err(Error::InvalidCandidate)
+        }
+
+        let candidate_key = VCounter::Counter(candidate.clone());
+        let d_votes: Vec<Symbol> = storage::get_voter_delegated_votes(&env, &voter);
+        let count = 1 + d_votes.len() + storage::get_candidate_votes_count(&env, &candidate_key);
+        let sealed_key = VCounter::Counter(storage::SEALED);
+        let unrevealed = storage::get_candidate_votes_count(&env, &sealed_key) - 1;
+
+        storage::update_candidate_count(&env, candidate_key, count);
+        storage::update_candidate_count(&env, sealed_key, unrevealed);
+        storage::remove_commitment(&env, &voter);
+        events::revealed(&env, voter, candidate, 1 + d_votes.len());
 
         Ok(true)
     }
@@ -136,25 +365,38 @@ impl Ballot {
         d_vot_delegs.push_back(o_voter.clone());
 
         storage::update_delegated_votes(&env, d_votes);
-        storage::update_voter_delegated_votes(&env, d_voter, d_vot_delegs);
+        storage::update_voter_delegated_votes(&env, d_voter.clone(), d_vot_delegs);
+        events::delegated(&env, o_voter, d_voter);
 
         Ok(true)
 
     }
 
+    /// Only the turnout is returned while the voting window is open, candidate counts once the ballot closes.
+    /// This does not make votes secret: vote events and contract storage are public, only commit-reveal
+    /// ballots keep votes hidden until they are revealed.
     pub fn count(env: Env,  admin: Address) -> Map<Symbol, u32> {
         
         admin.require_auth();
-        let pts = storage::get_candidates(&env);
-        let mut count_map: Map<Symbol, u32>= Map::new(&env);
-        for party in pts.iter() {
-            let candidate_key = VCounter::Counter(party.clone());
-            let candidate_count: u32 = storage::get_candidate_votes_count(&env, &candidate_key);
-            count_map.set(party, candidate_count);
+        let mut count_map: Map<Symbol, u32> = Map::new(&env);
+        if has_ended(&env) {
+            count_map = tally_map(&env);
+        } else {
+            count_map.set(storage::TURNOUT, storage::get_votes(&env).len());
         }
 
+        events::counted(&env, count_map.clone());
         count_map
     }
+
+    /// Public candidate counts, abstentions and turnout, available once the ballot is closed.
+    pub fn results(env: Env) -> Result<Map<Symbol, u32>, Error> {
+        if !has_ended(&env) {
+            return Err(Error::BallotNotClosed);
+        }
+
+        Ok(tally_map(&env))
+    }
 }
 
 mod test;
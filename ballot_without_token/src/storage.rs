@@ -1,21 +1,20 @@
-use soroban_sdk::{ Env, Symbol, Vec, symbol_short, contracttype};
+use soroban_sdk::{ Address, BytesN, Env, Symbol, Vec, symbol_short, contracttype};
 
 pub const VOTES: Symbol = symbol_short!("votes");
 pub const PARTIES: Symbol = symbol_short!("parties");
 pub const DVOTES: Symbol = symbol_short!("dvotes");
 pub const CONFIG: Symbol = symbol_short!("config");
+pub const TURNOUT: Symbol = symbol_short!("turnout");
+pub const ABSTAIN: Symbol = symbol_short!("abstain");
+pub const SEALED: Symbol = symbol_short!("sealed");
+pub const ADMIN: Symbol = symbol_short!("admin");
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 #[contracttype]
 pub struct Config {
     pub from: u64,
-    pub to: u64
-}
-
-impl Default for Config {
-    fn default () -> Config {
-        Config { from: 0, to: 0 }
-    }
+    pub to: u64,
+    pub reveal_period: u64
 }
 
 #[contracttype]
@@ -23,6 +22,11 @@ pub enum VCounter {
     Counter(Symbol)
 }
 
+#[contracttype]
+pub enum SealedKey {
+    Commitment(Symbol)
+}
+
 pub fn get_candidates(env: &Env) -> Vec<Symbol> {
     let pts: Vec<Symbol> = env
         .storage()
@@ -45,6 +49,17 @@ pub fn store_party(env: &Env, p: &Symbol) -> bool {
     false
 }
 
+pub fn remove_party(env: &Env, p: &Symbol) -> bool {
+    let mut pts: Vec<Symbol> = get_candidates(env);
+    if let Some(i) = pts.first_index_of(p) {
+        pts.remove(i);
+        env.storage().instance().set(&PARTIES, &pts);
+        return true;
+    }
+
+    false
+}
+
 pub fn get_votes(env: &Env) -> Vec<Symbol>{
     let vts: Vec<Symbol> = env
         .storage()
@@ -78,6 +93,7 @@ pub fn get_delegated_votes(env: &Env) -> Vec<Symbol> {
     dvts
 }
 
+#[allow(clippy::let_and_return)]
 pub fn get_candidate_votes_count(env: &Env, candidate: &VCounter) -> u32 {
     let total_votes = env.storage().instance().get(candidate).unwrap_or(0);
     total_votes
@@ -87,6 +103,18 @@ pub fn update_candidate_count(env: &Env, candidate: VCounter, count: u32) {
     env.storage().instance().set(&candidate, &count);
 }
 
+pub fn get_commitment(env: &Env, voter: &Symbol) -> Option<BytesN<32>> {
+    env.storage().instance().get(&SealedKey::Commitment(voter.clone()))
+}
+
+pub fn store_commitment(env: &Env, voter: &Symbol, commitment: &BytesN<32>) {
+    env.storage().instance().set(&SealedKey::Commitment(voter.clone()), commitment);
+}
+
+pub fn remove_commitment(env: &Env, voter: &Symbol) {
+    env.storage().instance().remove(&SealedKey::Commitment(voter.clone()));
+}
+
 pub fn update_votes(env: &Env, votes: Vec<Symbol>) {
     env.storage().instance().set(&VOTES, &votes);
 }
@@ -99,15 +127,17 @@ pub fn update_voter_delegated_votes(env: &Env, d_voter: Symbol, d_vot_delegs: Ve
     env.storage().instance().set(&d_voter, &d_vot_delegs);
 }
 
-pub fn store_config(env: &Env, ts_start: u64, ts_end: u64) {
+pub fn store_config(env: &Env, ts_start: u64, ts_end: u64, reveal_period: u64) {
     let cfg = Config {
         from: ts_start,
-        to: ts_end
+        to: ts_end,
+        reveal_period
     };
 
     env.storage().instance().set(&CONFIG, &cfg);
 }
 
+#[allow(clippy::let_and_return, clippy::unwrap_or_default)]
 pub fn get_config(env: &Env) -> Config {
     let cfg = env
         .storage()
@@ -117,4 +147,12 @@ pub fn get_config(env: &Env) -> Config {
     );
 
     cfg
+}
+
+pub fn get_admin(env: &Env) -> Option<Address> {
+    env.storage().instance().get(&ADMIN)
+}
+
+pub fn store_admin(env: &Env, admin: &Address) {
+    env.storage().instance().set(&ADMIN, admin);
 }
\ No newline at end of file
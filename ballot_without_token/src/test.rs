@@ -1,30 +1,54 @@
 #![cfg(test)]
 
-use super::{Ballot, BallotClient};
-use soroban_sdk::{Env, symbol_short, testutils::Address as _, Address};
+use super::{Ballot, BallotClient, Error};
+use soroban_sdk::{Env, symbol_short, testutils::{Address as _, Events, Ledger}, vec, xdr::ToXdr, Address, Bytes, BytesN, IntoVal, Symbol, Val, Vec};
+
+fn last_event(env: &Env) -> Vec<(Address, Vec<Val>, Val)> {
+    let events = env.events().all();
+    events.slice(events.len() - 1..)
+}
+
+fn candidates(env: &Env) -> Vec<Symbol> {
+    vec![env, symbol_short!("Laborist"), symbol_short!("Conserv"), symbol_short!("Liberal")]
+}
 
 #[test]
 fn vote_test() {
     let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
     let client = create_client(&env);
     let addr_admin = Address::generate(&env);
 
-    assert_eq!(client.vote(&addr_admin, &symbol_short!("hyyt76"), &symbol_short!("Laborist")), true);
-    assert_eq!(client.vote(&addr_admin, &symbol_short!("ptft37"), &symbol_short!("Conserv")), true);
-    assert_eq!(client.vote(&addr_admin, &symbol_short!("oo9gt6"), &symbol_short!("Conserv")), true);
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    client.configure(&addr_admin, &0, &0, &0);
+    client.register_candidates(&addr_admin, &candidates(&env));
+    client.configure(&addr_admin, &ts_start, &ts_end, &0);
+
+    assert!(client.vote(&addr_admin, &symbol_short!("hyyt76"), &symbol_short!("Laborist")));
+    assert!(client.vote(&addr_admin, &symbol_short!("ptft37"), &symbol_short!("Conserv")));
+    assert!(client.vote(&addr_admin, &symbol_short!("oo9gt6"), &symbol_short!("Conserv")));
+    assert_eq!(
+        client.try_commit_vote(&addr_admin, &symbol_short!("oonvv5"), &BytesN::from_array(&env, &[0; 32])),
+        Err(Ok(Error::InvalidBallotMode))
+    );
 
     let count = client.count(&addr_admin);
 
-    assert_eq!(count.get(symbol_short!("Laborist")).unwrap(), 1);
-    assert_eq!(count.get(symbol_short!("Conserv")).unwrap(), 2);
+    assert_eq!(count.len(), 1);
+    assert_eq!(count.get(symbol_short!("turnout")).unwrap(), 3);
 
     client.delegate(&addr_admin, &symbol_short!("ippcxs"), &symbol_short!("oonvv5"));
-    assert_eq!(client.vote(&addr_admin, &symbol_short!("oonvv5"), &symbol_short!("Conserv")), true);
+    assert!(client.vote(&addr_admin, &symbol_short!("oonvv5"), &symbol_short!("Conserv")));
 
-    let count = client.count(&addr_admin);
+    env.ledger().with_mut(|l| {l.timestamp = ts_end + 1;});
+    let count = client.results();
 
     assert_eq!(count.get(symbol_short!("Laborist")).unwrap(), 1);
     assert_eq!(count.get(symbol_short!("Conserv")).unwrap(),4);
+    assert_eq!(count.get(symbol_short!("turnout")).unwrap(), 4);
+    assert_eq!(client.count(&addr_admin), count);
 
 }
 
@@ -38,7 +62,7 @@ fn vote_out_of_dates_test() {
     let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
     let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
 
-    client.configure(&addr_admin, &ts_start, &ts_end);
+    client.configure(&addr_admin, &ts_start, &ts_end, &0);
     client.vote(&addr_admin, &symbol_short!("hyyt76"), &symbol_short!("Laborist"));
 }
 
@@ -49,6 +73,8 @@ fn vote_test_already_voted() {
     let client = create_client(&env);
     let addr_admin = Address::generate(&env);
 
+    client.configure(&addr_admin, &0, &0, &0);
+    client.register_candidates(&addr_admin, &candidates(&env));
     client.vote(&addr_admin, &symbol_short!("hyyt76"), &symbol_short!("Laborist"));
     client.vote(&addr_admin, &symbol_short!("hyyt76"), &symbol_short!("Laborist"));
 }
@@ -93,6 +119,8 @@ fn delegate_test_o_voter_has_voted() {
     let client = create_client(&env);
 
     let addr_admin = Address::generate(&env);
+    client.configure(&addr_admin, &0, &0, &0);
+    client.register_candidates(&addr_admin, &candidates(&env));
     client.vote(&addr_admin, &symbol_short!("ippcxs"), &symbol_short!("Laborist"));
     client.delegate(&addr_admin, &symbol_short!("ippcxs"), &symbol_short!("hhcfrp"));
 }
@@ -104,14 +132,243 @@ fn delegate_test_d_voter_has_voted() {
     let client = create_client(&env);
 
     let addr_admin = Address::generate(&env);
+    client.configure(&addr_admin, &0, &0, &0);
+    client.register_candidates(&addr_admin, &candidates(&env));
     client.vote(&addr_admin, &symbol_short!("hhcfrp"), &symbol_short!("Laborist"));
     client.delegate(&addr_admin, &symbol_short!("ippcxs"), &symbol_short!("hhcfrp"));
 }
 
-fn create_client(env: &Env) -> BallotClient{
+#[test]
+fn register_and_remove_candidates_test() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let addr_admin = Address::generate(&env);
+
+    client.configure(&addr_admin, &0, &0, &0);
+    client.register_candidates(&addr_admin, &candidates(&env));
+    client.register_candidates(&addr_admin, &vec![&env, symbol_short!("Laborist"), symbol_short!("Green")]);
+    assert!(client.remove_candidate(&addr_admin, &symbol_short!("Liberal")));
+
+    assert_eq!(client.candidates(), vec![&env, symbol_short!("Laborist"), symbol_short!("Conserv"), symbol_short!("Green")]);
+}
+
+#[test]
+fn non_admin_cannot_manage_candidates_test() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let addr_admin = Address::generate(&env);
+    let intruder = Address::generate(&env);
+
+    assert_eq!(client.try_register_candidates(&intruder, &candidates(&env)), Err(Ok(Error::BallotNotConfigured)));
+
+    client.configure(&addr_admin, &0, &0, &0);
+    client.register_candidates(&addr_admin, &candidates(&env));
+    assert_eq!(client.try_register_candidates(&intruder, &vec![&env, symbol_short!("Green")]), Err(Ok(Error::AddressIsNotAdmin)));
+    assert_eq!(client.try_remove_candidate(&intruder, &symbol_short!("Liberal")), Err(Ok(Error::AddressIsNotAdmin)));
+    assert_eq!(client.try_configure(&intruder, &0, &0, &0), Err(Ok(Error::AddressIsNotAdmin)));
+    assert_eq!(client.candidates(), candidates(&env));
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #7)")]
+fn remove_unregistered_candidate_test() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let addr_admin = Address::generate(&env);
+
+    client.configure(&addr_admin, &0, &0, &0);
+    client.register_candidates(&addr_admin, &candidates(&env));
+    client.remove_candidate(&addr_admin, &symbol_short!("Green"));
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #8)")]
+fn register_candidates_once_ballot_opened_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    let client = create_client(&env);
+    let addr_admin = Address::generate(&env);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    client.configure(&addr_admin, &ts_start, &ts_end, &0);
+    client.register_candidates(&addr_admin, &candidates(&env));
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #7)")]
+fn vote_unregistered_candidate_test() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let addr_admin = Address::generate(&env);
+
+    client.configure(&addr_admin, &0, &0, &0);
+    client.register_candidates(&addr_admin, &candidates(&env));
+    client.vote(&addr_admin, &symbol_short!("hyyt76"), &symbol_short!("Laboristt"));
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #9)")]
+fn results_before_close_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    let client = create_client(&env);
+    let addr_admin = Address::generate(&env);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    client.configure(&addr_admin, &ts_start, &ts_end, &0);
+    client.results();
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #7)")]
+fn register_reserved_candidate_test() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let addr_admin = Address::generate(&env);
+
+    client.configure(&addr_admin, &0, &0, &0);
+    client.register_candidates(&addr_admin, &vec![&env, symbol_short!("turnout")]);
+}
+
+#[test]
+fn abstain_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    let client = create_client(&env);
+    let addr_admin = Address::generate(&env);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    client.configure(&addr_admin, &0, &0, &0);
+    client.register_candidates(&addr_admin, &candidates(&env));
+    client.configure(&addr_admin, &ts_start, &ts_end, &0);
+
+    client.vote(&addr_admin, &symbol_short!("hyyt76"), &symbol_short!("Laborist"));
+    client.delegate(&addr_admin, &symbol_short!("ippcxs"), &symbol_short!("oonvv5"));
+    assert_eq!(client.try_abstain(&Address::generate(&env), &symbol_short!("oonvv5")), Err(Ok(Error::AddressIsNotAdmin)));
+    assert!(client.abstain(&addr_admin, &symbol_short!("oonvv5")));
+    assert_eq!(
+        last_event(&env),
+        vec![
+            &env,
+            (
+                client.address.clone(),
+                (symbol_short!("ballot"), symbol_short!("abstained"), symbol_short!("oonvv5")).into_val(&env),
+                2_u32.into_val(&env)
+            )
+        ]
+    );
+
+    env.ledger().with_mut(|l| {l.timestamp = ts_end + 1;});
+    let count = client.results();
+    assert_eq!(count.get(symbol_short!("Laborist")).unwrap(), 1);
+    assert_eq!(count.get(symbol_short!("abstain")).unwrap(), 2);
+    assert_eq!(count.get(symbol_short!("turnout")).unwrap(), 2);
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #1)")]
+fn abstain_delegated_vote_test() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let addr_admin = Address::generate(&env);
+
+    client.configure(&addr_admin, &0, &0, &0);
+    client.delegate(&addr_admin, &symbol_short!("ippcxs"), &symbol_short!("oonvv5"));
+    client.abstain(&addr_admin, &symbol_short!("ippcxs"));
+}
+
+#[test]
+fn vote_and_delegate_events_test() {
+    let env = Env::default();
+    let client = create_client(&env);
+    let addr_admin = Address::generate(&env);
+
+    client.configure(&addr_admin, &0, &0, &0);
+    client.register_candidates(&addr_admin, &candidates(&env));
+    client.delegate(&addr_admin, &symbol_short!("ippcxs"), &symbol_short!("oonvv5"));
+    assert_eq!(
+        last_event(&env),
+        vec![
+            &env,
+            (
+                client.address.clone(),
+                (symbol_short!("ballot"), symbol_short!("delegated"), symbol_short!("ippcxs")).into_val(&env),
+                symbol_short!("oonvv5").into_val(&env)
+            )
+        ]
+    );
+
+    client.vote(&addr_admin, &symbol_short!("oonvv5"), &symbol_short!("Conserv"));
+    assert_eq!(
+        last_event(&env),
+        vec![
+            &env,
+            (
+                client.address.clone(),
+                (symbol_short!("ballot"), symbol_short!("voted"), symbol_short!("oonvv5")).into_val(&env),
+                (symbol_short!("Conserv"), 2_u32).into_val(&env)
+            )
+        ]
+    );
+}
+
+fn seal(env: &Env, candidate: Symbol, salt: &BytesN<32>) -> BytesN<32> {
+    let mut data = candidate.to_xdr(env);
+    data.append(&Bytes::from_array(env, &salt.to_array()));
+    env.crypto().sha256(&data)
+}
+
+#[test]
+fn commit_reveal_test() {
+    let env = Env::default();
+    env.ledger().with_mut(|l| {l.timestamp = 1689238844;});
+    let client = create_client(&env);
+    let addr_admin = Address::generate(&env);
+    let salt = BytesN::from_array(&env, &[7; 32]);
+
+    let ts_start: u64 = 1689238800; // 2023-07-13 09:00:00
+    let ts_end: u64 = 1689551999; // 2023-07-16 23:59:59
+
+    client.configure(&addr_admin, &0, &0, &0);
+    client.register_candidates(&addr_admin, &candidates(&env));
+    client.configure(&addr_admin, &ts_start, &ts_end, &86400);
+
+    client.delegate(&addr_admin, &symbol_short!("ippcxs"), &symbol_short!("hyyt76"));
+    client.commit_vote(&addr_admin, &symbol_short!("hyyt76"), &seal(&env, symbol_short!("Laborist"), &salt));
+    client.commit_vote(&addr_admin, &symbol_short!("ptft37"), &seal(&env, symbol_short!("Conserv"), &salt));
+    client.commit_vote(&addr_admin, &symbol_short!("oo9gt6"), &seal(&env, symbol_short!("Conserv"), &salt));
+    assert_eq!(client.try_vote(&addr_admin, &symbol_short!("oonvv5"), &symbol_short!("Conserv")), Err(Ok(Error::CommitRevealBallot)));
+    assert_eq!(client.try_reveal_vote(&addr_admin, &symbol_short!("hyyt76"), &symbol_short!("Laborist"), &salt), Err(Ok(Error::BallotOutOfDate)));
+    assert_eq!(client.try_commit_vote(&Address::generate(&env), &symbol_short!("oonvv5"), &seal(&env, symbol_short!("Conserv"), &salt)), Err(Ok(Error::AddressIsNotAdmin)));
+    assert_eq!(client.try_configure(&addr_admin, &ts_start, &ts_end, &0), Err(Ok(Error::BallotAlreadyStarted)));
+    client.configure(&addr_admin, &ts_start, &ts_end, &86400);
+
+    env.ledger().with_mut(|l| {l.timestamp = ts_end + 1;});
+    assert_eq!(client.try_results(), Err(Ok(Error::BallotNotClosed)));
+    assert_eq!(client.try_reveal_vote(&addr_admin, &symbol_short!("ptft37"), &symbol_short!("Liberal"), &salt), Err(Ok(Error::InvalidReveal)));
+    assert_eq!(client.try_reveal_vote(&Address::generate(&env), &symbol_short!("hyyt76"), &symbol_short!("Laborist"), &salt), Err(Ok(Error::AddressIsNotAdmin)));
+    client.reveal_vote(&addr_admin, &symbol_short!("hyyt76"), &symbol_short!("Laborist"), &salt);
+    client.reveal_vote(&addr_admin, &symbol_short!("ptft37"), &symbol_short!("Conserv"), &salt);
+    assert_eq!(client.try_reveal_vote(&addr_admin, &symbol_short!("ptft37"), &symbol_short!("Conserv"), &salt), Err(Ok(Error::VoteNotCommitted)));
+
+    env.ledger().with_mut(|l| {l.timestamp = ts_end + 86401;});
+    let count = client.results();
+    assert_eq!(count.get(symbol_short!("Laborist")).unwrap(), 2);
+    assert_eq!(count.get(symbol_short!("Conserv")).unwrap(), 1);
+    assert_eq!(count.get(symbol_short!("sealed")).unwrap(), 1);
+    assert_eq!(count.get(symbol_short!("turnout")).unwrap(), 3);
+}
+
+fn create_client(env: &Env) -> BallotClient<'_>{
     env.mock_all_auths();
 
     let contract_id = env.register_contract(None, Ballot);
-    let client = BallotClient::new(&env, &contract_id);
+    let client = BallotClient::new(env, &contract_id);
     client
 }
\ No newline at end of file